@@ -9,7 +9,7 @@ use std::{borrow::Cow, future::Future, io::ErrorKind, pin::Pin};
 use anyhow::{bail, ensure, Context};
 use bytes::Bytes;
 use camino::{Utf8Path, Utf8PathBuf};
-use futures::stream::Stream;
+use futures::stream::{Stream, StreamExt};
 use tokio::{
     fs,
     io::{self, AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
@@ -24,11 +24,159 @@ use super::{RemoteStorage, StorageMetadata};
 
 const LOCAL_FS_TEMP_FILE_SUFFIX: &str = "___temp";
 
+/// Default page size for `list_streaming`, emulating S3's 1000-key continuation-token behavior.
+const DEFAULT_MAX_KEYS_PER_PAGE: usize = 1000;
+
+/// Reserved key in the `StorageMetadata` sidecar under which the content digest is stored, as
+/// `"<algorithm>:<hex digest>"`, e.g. `"crc32c:1a2b3c4d"`.
+const CHECKSUM_METADATA_KEY: &str = "x-neon-checksum";
+
+/// Content-integrity algorithm used to checksum an object's bytes on upload, so that bit rot or
+/// truncation on disk can be detected on download instead of silently returning corrupt data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Crc32c,
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    fn name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc32c => "crc32c",
+            ChecksumAlgorithm::Sha256 => "sha256",
+        }
+    }
+
+    fn encode(&self, digest_hex: &str) -> String {
+        format!("{}:{}", self.name(), digest_hex)
+    }
+}
+
+/// Accumulates a `ChecksumAlgorithm` digest incrementally as chunks stream past, so
+/// `upload_with_checksum`/`download_verifying_checksum` never need to buffer a whole object
+/// in memory just to hash it.
+enum ChecksumHasher {
+    Crc32c(u32),
+    Sha256(sha2::Sha256),
+}
+
+impl ChecksumHasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Crc32c => ChecksumHasher::Crc32c(0),
+            ChecksumAlgorithm::Sha256 => ChecksumHasher::Sha256(sha2::Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, chunk: &[u8]) {
+        match self {
+            ChecksumHasher::Crc32c(crc) => *crc = crc32c::crc32c_append(*crc, chunk),
+            ChecksumHasher::Sha256(hasher) => {
+                use sha2::Digest;
+                hasher.update(chunk);
+            }
+        }
+    }
+
+    fn finish(self) -> String {
+        match self {
+            ChecksumHasher::Crc32c(crc) => format!("{crc:08x}"),
+            ChecksumHasher::Sha256(hasher) => {
+                use sha2::Digest;
+                hasher.finalize().iter().map(|b| format!("{b:02x}")).collect()
+            }
+        }
+    }
+}
+
+/// Metadata-only view of a stored object, returned by `head_object` without fetching its bytes.
+/// Mirrors what object-store APIs return from a HEAD request, including the `StorageMetadata`
+/// sidecar if the backend carries one, so callers can validate expected layer sizes and existence
+/// (and read back custom metadata) without the `download_byte_range(0, Some(1))` hack. Backends
+/// that don't track a sidecar (e.g. Azure, when the blob has none) report `metadata: None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectMetadata {
+    pub size: u64,
+    pub last_modified: std::time::SystemTime,
+    pub metadata: Option<StorageMetadata>,
+}
+
+/// Opaque handle identifying an in-progress [`LocalFs::start_multipart_upload`] operation.
+///
+/// Unlike [`RemoteStorage::upload`], multipart uploads don't require the caller to know the
+/// total size up front: bytes are streamed into a temporary file as they become available, and
+/// the upload is only made visible at the target path once `complete` is called.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipartUploadId(String);
+
+impl std::fmt::Display for MultipartUploadId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Ergonomic handle over `start_multipart_upload`/`complete_multipart_upload`/`abort_multipart_upload`,
+/// so callers streaming a large object (e.g. the pageserver writing out a layer file of unknown
+/// size) don't need to juggle the raw writer and upload id themselves. Chunks are appended via
+/// `write_part` and buffered straight into the temp file; `complete`/`abort` finalize or roll back
+/// the upload, mirroring S3's `UploadPart` + `CompleteMultipartUpload`/`AbortMultipartUpload`.
+pub struct MultipartUpload<'a> {
+    storage: &'a LocalFs,
+    to: RemotePath,
+    upload_id: MultipartUploadId,
+    writer: Pin<Box<dyn io::AsyncWrite + Send + Sync>>,
+}
+
+impl<'a> MultipartUpload<'a> {
+    pub async fn new(storage: &'a LocalFs, to: RemotePath) -> anyhow::Result<Self> {
+        let (upload_id, writer) = storage.start_multipart_upload(&to).await?;
+        Ok(Self {
+            storage,
+            to,
+            upload_id,
+            writer,
+        })
+    }
+
+    /// Buffers another chunk of unknown-length data into the upload. This never needs the
+    /// caller to know the total object size up front.
+    pub async fn write_part(&mut self, chunk: Bytes) -> anyhow::Result<()> {
+        self.writer.write_all(&chunk).await?;
+        Ok(())
+    }
+
+    pub async fn complete(mut self) -> anyhow::Result<()> {
+        self.writer.flush().await?;
+        self.storage
+            .complete_multipart_upload(&self.to, &self.upload_id)
+            .await
+    }
+
+    pub async fn abort(self) -> anyhow::Result<()> {
+        self.storage
+            .abort_multipart_upload(&self.to, &self.upload_id)
+            .await
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LocalFs {
     storage_root: Utf8PathBuf,
 }
 
+// Paging state for a `ListingMode::NoDelimiter` walk: the same directory_queue BFS
+// `list_recursive` used to run to completion before returning, except now the queue (and a
+// small buffer of not-yet-emitted keys) lives across `list_streaming` polls, so the whole
+// prefix is never materialized at once. Each directory is read in full when it is visited, so
+// the buffer can hold up to a page's worth of keys plus one directory's files.
+struct RecursiveListCursor {
+    // Note that Utf8PathBuf starts_with only considers full path segments, but object
+    // prefixes are arbitrary strings, so we need the string for doing starts_with later.
+    prefix: String,
+    directory_queue: Vec<Utf8PathBuf>,
+    pending_keys: Vec<RemotePath>,
+}
+
 impl LocalFs {
     /// Attempts to create local FS storage, along with its root directory.
     /// Storage root will be created (if does not exist) and transformed into an absolute path (if passed as relative).
@@ -77,6 +225,21 @@ impl LocalFs {
         }
     }
 
+    async fn write_storage_metadata(
+        &self,
+        file_path: &Utf8Path,
+        metadata: &StorageMetadata,
+    ) -> anyhow::Result<()> {
+        let metadata_path = storage_metadata_path(file_path);
+        fs::write(
+            &metadata_path,
+            serde_json::to_string(&metadata.0)
+                .context("Failed to serialize storage metadata as json")?,
+        )
+        .await
+        .with_context(|| format!("Failed to write metadata to the local storage at '{metadata_path}'"))
+    }
+
     #[cfg(test)]
     async fn list_all(&self) -> anyhow::Result<Vec<RemotePath>> {
         Ok(get_all_files(&self.storage_root, true)
@@ -93,9 +256,189 @@ impl LocalFs {
             .collect())
     }
 
-    // recursively lists all files in a directory,
-    // mirroring the `list_files` for `s3_bucket`
-    async fn list_recursive(&self, folder: Option<&RemotePath>) -> anyhow::Result<Vec<RemotePath>> {
+    // mirrors the pre-streaming `list` body for `ListingMode::WithDelimiter`: walks one level of
+    // the prefix and returns a fully materialized `Listing`, which `list_streaming` then slices
+    // into pages. `ListingMode::NoDelimiter` is handled separately by `RecursiveListCursor`,
+    // which pages directly off the BFS queue instead of materializing the whole tree.
+    async fn list_all_entries(&self, prefix: Option<&RemotePath>) -> Result<Listing, DownloadError> {
+        let mut result = Listing::default();
+
+        let path = match prefix {
+            Some(prefix) => Cow::Owned(prefix.with_base(&self.storage_root)),
+            None => Cow::Borrowed(&self.storage_root),
+        };
+
+        let prefixes_to_filter = get_all_files(path.as_ref(), false)
+            .await
+            .map_err(DownloadError::Other)?;
+
+        // filter out empty directories to mirror s3 behavior.
+        for prefix in prefixes_to_filter {
+            if prefix.is_dir()
+                && is_directory_empty(&prefix)
+                    .await
+                    .map_err(DownloadError::Other)?
+            {
+                continue;
+            }
+
+            let stripped = prefix
+                .strip_prefix(&self.storage_root)
+                .context("Failed to strip prefix")
+                .and_then(RemotePath::new)
+                .expect(
+                    "We list files for storage root, hence should be able to remote the prefix",
+                );
+
+            if prefix.is_dir() {
+                result.prefixes.push(stripped);
+            } else {
+                result.keys.push(stripped);
+            }
+        }
+
+        Ok(result)
+    }
+
+    // mirrors the temp-file-then-rename dance in `upload`, but the temp file name carries a
+    // random nonce instead of the fixed `LOCAL_FS_TEMP_FILE_SUFFIX`, so that two concurrent
+    // multipart uploads to the same target never clobber each other's temp file.
+    fn multipart_temp_path(&self, to: &RemotePath) -> (MultipartUploadId, Utf8PathBuf) {
+        let target_file_path = to.with_base(&self.storage_root);
+        let nonce: u64 = rand::random();
+        let upload_id = MultipartUploadId(format!("{nonce:016x}"));
+        let temp_file_path = path_with_suffix_extension(
+            &target_file_path,
+            &format!("{LOCAL_FS_TEMP_FILE_SUFFIX}-{upload_id}"),
+        );
+        (upload_id, temp_file_path)
+    }
+
+    // gives each copy its own nonce-suffixed temp file path, so that concurrent copies to the
+    // same target (via `copy` or `copy_if_not_exists`) never clobber each other's temp file,
+    // mirroring `multipart_temp_path`'s nonce scheme.
+    fn copy_temp_path(&self, to_path: &Utf8Path) -> Utf8PathBuf {
+        let nonce: u64 = rand::random();
+        path_with_suffix_extension(to_path, &format!("{LOCAL_FS_TEMP_FILE_SUFFIX}-{nonce:016x}"))
+    }
+
+    /// Like `upload`, but additionally computes a `checksum_algorithm` digest over the bytes as
+    /// they stream through and persists it in the metadata sidecar, so `download_verifying_checksum`
+    /// can later detect on-disk corruption.
+    pub async fn upload_with_checksum(
+        &self,
+        data: impl Stream<Item = std::io::Result<Bytes>> + Send + Sync,
+        data_size_bytes: usize,
+        to: &RemotePath,
+        metadata: Option<StorageMetadata>,
+        checksum_algorithm: ChecksumAlgorithm,
+    ) -> anyhow::Result<()> {
+        let target_file_path = to.with_base(&self.storage_root);
+        create_target_directory(&target_file_path).await?;
+        let temp_file_path =
+            path_with_suffix_extension(&target_file_path, LOCAL_FS_TEMP_FILE_SUFFIX);
+        let mut destination = io::BufWriter::new(
+            fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .open(&temp_file_path)
+                .await
+                .with_context(|| {
+                    format!("Failed to open target fs destination at '{target_file_path}'")
+                })?,
+        );
+
+        // Hash each chunk as it arrives and write it straight into the destination, instead of
+        // buffering the whole object first: the whole point of a checksummed upload is to detect
+        // corruption in a multi-GB layer without ever materializing it in memory.
+        let mut hasher = ChecksumHasher::new(checksum_algorithm);
+        let mut bytes_written = 0usize;
+        let mut data = std::pin::pin!(data);
+        while let Some(chunk) = data.next().await {
+            let chunk = chunk.context("Failed to read from the upload source stream")?;
+            hasher.update(&chunk);
+            destination.write_all(&chunk).await.with_context(|| {
+                format!("Failed to upload (write temp) file to the local storage at '{temp_file_path}'")
+            })?;
+            bytes_written += chunk.len();
+        }
+        ensure!(
+            bytes_written == data_size_bytes,
+            "Provided stream had {bytes_written} bytes, expected {data_size_bytes}",
+        );
+
+        destination.flush().await.with_context(|| {
+            format!("Failed to upload (flush temp) file to the local storage at '{temp_file_path}'")
+        })?;
+
+        fs::rename(&temp_file_path, &target_file_path)
+            .await
+            .with_context(|| {
+                format!("Failed to upload (rename) file to the local storage at '{target_file_path}'")
+            })?;
+
+        let mut metadata = metadata.unwrap_or_else(|| StorageMetadata(Default::default()));
+        metadata.0.insert(
+            CHECKSUM_METADATA_KEY.to_string(),
+            checksum_algorithm.encode(&hasher.finish()),
+        );
+        self.write_storage_metadata(&target_file_path, &metadata)
+            .await?;
+
+        debug!(bytes = bytes_written, "upload_with_checksum completed");
+
+        Ok(())
+    }
+
+    /// Like `download`, but if the metadata sidecar carries a checksum (written by
+    /// `upload_with_checksum`), re-verifies the downloaded bytes against it chunk-by-chunk as the
+    /// returned stream is consumed, rather than buffering the whole object up front to check it
+    /// before returning. A mismatch can only be detected once the stream is fully drained, so it
+    /// surfaces as an `io::Error` on the *last* item of `download_stream`: callers must treat a
+    /// checksummed download as untrusted until the stream ends without error.
+    pub async fn download_verifying_checksum(
+        &self,
+        from: &RemotePath,
+    ) -> Result<Download, DownloadError> {
+        let download = self.download(from).await?;
+        let Some(checksum) = download
+            .metadata
+            .as_ref()
+            .and_then(|m| m.0.get(CHECKSUM_METADATA_KEY))
+            .cloned()
+        else {
+            return Ok(download);
+        };
+        let Some((algorithm, expected_digest)) = checksum.split_once(':') else {
+            return Ok(download);
+        };
+        let algorithm = match algorithm {
+            "crc32c" => ChecksumAlgorithm::Crc32c,
+            "sha256" => ChecksumAlgorithm::Sha256,
+            other => {
+                return Err(DownloadError::Other(anyhow::anyhow!(
+                    "Unknown checksum algorithm '{other}' in metadata sidecar"
+                )))
+            }
+        };
+
+        Ok(Download {
+            metadata: download.metadata.clone(),
+            download_stream: verify_checksum_stream(
+                download.download_stream,
+                algorithm,
+                expected_digest.to_string(),
+                from.clone(),
+            ),
+        })
+    }
+
+    // mirrors the `list_files` ancestor-walk for `s3_bucket`, then seeds a `RecursiveListCursor`
+    // instead of eagerly walking the whole tree.
+    async fn recursive_list_cursor(
+        &self,
+        folder: Option<&RemotePath>,
+    ) -> anyhow::Result<RecursiveListCursor> {
         let full_path = match folder {
             Some(folder) => folder.with_base(&self.storage_root),
             None => self.storage_root.clone(),
@@ -131,95 +474,171 @@ impl LocalFs {
                 }
             }
         }
-        // Note that Utf8PathBuf starts_with only considers full path segments, but
-        // object prefixes are arbitrary strings, so we need the strings for doing
-        // starts_with later.
-        let prefix = full_path.as_str();
 
-        let mut files = vec![];
-        let mut directory_queue = vec![initial_dir];
-        while let Some(cur_folder) = directory_queue.pop() {
+        Ok(RecursiveListCursor {
+            prefix: full_path.as_str().to_owned(),
+            directory_queue: vec![initial_dir],
+            pending_keys: Vec::new(),
+        })
+    }
+
+    // Advances the BFS queue, reading one directory at a time, until either `max_keys` keys have
+    // been buffered or the queue is exhausted, then drains up to `max_keys` of them into a page.
+    // Keeps the same prefix-filtering, subdirectory-traversal and directories-aren't-keys
+    // semantics `list_recursive` had. A directory is always read in full, so a directory with
+    // more than `max_keys` files leaves the surplus buffered for later pages.
+    fn recursive_list_page(
+        &self,
+        cursor: &mut RecursiveListCursor,
+        max_keys: usize,
+    ) -> anyhow::Result<Vec<RemotePath>> {
+        while cursor.pending_keys.len() < max_keys {
+            let Some(cur_folder) = cursor.directory_queue.pop() else {
+                break;
+            };
             let mut entries = cur_folder.read_dir_utf8()?;
             while let Some(Ok(entry)) = entries.next() {
                 let file_name = entry.file_name();
                 let full_file_name = cur_folder.join(file_name);
-                if full_file_name.as_str().starts_with(prefix) {
-                    let file_remote_path = self.local_file_to_relative_path(full_file_name.clone());
-                    files.push(file_remote_path);
+                if full_file_name.as_str().starts_with(cursor.prefix.as_str()) {
                     if full_file_name.is_dir() {
-                        directory_queue.push(full_file_name);
+                        cursor.directory_queue.push(full_file_name);
+                    } else {
+                        cursor
+                            .pending_keys
+                            .push(self.local_file_to_relative_path(full_file_name));
                     }
                 }
             }
         }
 
-        Ok(files)
+        Ok(drain_prefix(&mut cursor.pending_keys, max_keys))
+    }
+
+    /// Moves `from` to `to`, built on top of `copy` + `delete` (S3 instead issues a `CopyObject`
+    /// followed by a `DeleteObject`, since it has no atomic rename primitive either). Used to
+    /// relocate timeline layers or promote staged uploads without re-streaming the file.
+    pub async fn rename(&self, from: &RemotePath, to: &RemotePath) -> anyhow::Result<()> {
+        self.copy(from, to).await?;
+        self.delete(from).await?;
+        // `delete` only removes the object itself; remove the source's metadata sidecar too, so
+        // the metadata moves along with the object instead of being left behind.
+        let from_metadata_path = storage_metadata_path(&from.with_base(&self.storage_root));
+        match fs::remove_file(&from_metadata_path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(anyhow::anyhow!(e)),
+        }
     }
 }
 
 #[async_trait::async_trait]
 impl RemoteStorage for LocalFs {
+    #[tracing::instrument(skip(self), fields(prefix = %prefix.map(|p| p.get_path().as_str().to_string()).unwrap_or_default()))]
     async fn list(
         &self,
         prefix: Option<&RemotePath>,
         mode: ListingMode,
     ) -> Result<Listing, DownloadError> {
         let mut result = Listing::default();
-
-        if let ListingMode::NoDelimiter = mode {
-            let keys = self
-                .list_recursive(prefix)
-                .await
-                .map_err(DownloadError::Other)?;
-
-            result.keys = keys
-                .into_iter()
-                .filter(|k| {
-                    let path = k.with_base(&self.storage_root);
-                    !path.is_dir()
-                })
-                .collect();
-
-            return Ok(result);
+        let mut pages = std::pin::pin!(self.list_streaming(prefix, mode));
+        while let Some(page) = pages.next().await {
+            let page = page?;
+            result.keys.extend(page.keys);
+            result.prefixes.extend(page.prefixes);
         }
+        debug!(
+            keys = result.keys.len(),
+            prefixes = result.prefixes.len(),
+            "list completed"
+        );
+        Ok(result)
+    }
 
-        let path = match prefix {
-            Some(prefix) => Cow::Owned(prefix.with_base(&self.storage_root)),
-            None => Cow::Borrowed(&self.storage_root),
-        };
-
-        let prefixes_to_filter = get_all_files(path.as_ref(), false)
-            .await
-            .map_err(DownloadError::Other)?;
-
-        // filter out empty directories to mirror s3 behavior.
-        for prefix in prefixes_to_filter {
-            if prefix.is_dir()
-                && is_directory_empty(&prefix)
-                    .await
-                    .map_err(DownloadError::Other)?
-            {
-                continue;
-            }
-
-            let stripped = prefix
-                .strip_prefix(&self.storage_root)
-                .context("Failed to strip prefix")
-                .and_then(RemotePath::new)
-                .expect(
-                    "We list files for storage root, hence should be able to remote the prefix",
-                );
-
-            if prefix.is_dir() {
-                result.prefixes.push(stripped);
-            } else {
-                result.keys.push(stripped);
-            }
+    /// Like `list`, but yields bounded pages of at most `DEFAULT_MAX_KEYS_PER_PAGE` keys instead
+    /// of buffering the whole prefix into memory, emulating S3's 1000-key continuation-token
+    /// behavior for large prefixes.
+    fn list_streaming<'a>(
+        &'a self,
+        prefix: Option<&'a RemotePath>,
+        mode: ListingMode,
+    ) -> Pin<Box<dyn Stream<Item = Result<Listing, DownloadError>> + Send + 'a>> {
+        // `NoDelimiter` pages directly off a `RecursiveListCursor`'s BFS queue, so a prefix with
+        // millions of keys holds at most a page's worth of `RemotePath`s plus the files of the
+        // directory last read in memory at a time.
+        // `WithDelimiter` only ever walks one directory level deep, so the existing
+        // materialize-then-slice approach via `list_all_entries` stays bounded enough as-is.
+        enum StreamState {
+            NotStarted,
+            Recursive(RecursiveListCursor),
+            Delimited(Listing),
+            Done,
         }
 
-        Ok(result)
+        Box::pin(futures::stream::unfold(
+            StreamState::NotStarted,
+            move |state| async move {
+                let mut state = match state {
+                    StreamState::NotStarted => match mode {
+                        ListingMode::NoDelimiter => match self.recursive_list_cursor(prefix).await
+                        {
+                            Ok(cursor) => StreamState::Recursive(cursor),
+                            Err(e) => return Some((Err(DownloadError::Other(e)), StreamState::Done)),
+                        },
+                        _ => match self.list_all_entries(prefix).await {
+                            Ok(entries) => StreamState::Delimited(entries),
+                            Err(e) => return Some((Err(e), StreamState::Done)),
+                        },
+                    },
+                    other => other,
+                };
+
+                match &mut state {
+                    StreamState::Recursive(cursor) => {
+                        let page_keys =
+                            match self.recursive_list_page(cursor, DEFAULT_MAX_KEYS_PER_PAGE) {
+                                Ok(keys) => keys,
+                                Err(e) => {
+                                    return Some((Err(DownloadError::Other(e)), StreamState::Done))
+                                }
+                            };
+                        if page_keys.is_empty() {
+                            return None;
+                        }
+                        Some((
+                            Ok(Listing {
+                                keys: page_keys,
+                                prefixes: Vec::new(),
+                            }),
+                            state,
+                        ))
+                    }
+                    StreamState::Delimited(entries) => {
+                        if entries.keys.is_empty() && entries.prefixes.is_empty() {
+                            return None;
+                        }
+
+                        let keys_in_page =
+                            DEFAULT_MAX_KEYS_PER_PAGE.saturating_sub(entries.prefixes.len());
+                        let page_keys = drain_prefix(&mut entries.keys, keys_in_page);
+                        let page_prefixes =
+                            drain_prefix(&mut entries.prefixes, DEFAULT_MAX_KEYS_PER_PAGE);
+
+                        Some((
+                            Ok(Listing {
+                                keys: page_keys,
+                                prefixes: page_prefixes,
+                            }),
+                            state,
+                        ))
+                    }
+                    StreamState::NotStarted | StreamState::Done => None,
+                }
+            },
+        ))
     }
 
+    #[tracing::instrument(skip(self, data), fields(path = %to, size = data_size_bytes, metadata_keys = metadata.as_ref().map(|m| m.0.len()).unwrap_or(0)))]
     async fn upload(
         &self,
         data: impl Stream<Item = std::io::Result<Bytes>> + Send + Sync,
@@ -294,23 +713,111 @@ impl RemoteStorage for LocalFs {
             })?;
 
         if let Some(storage_metadata) = metadata {
-            let storage_metadata_path = storage_metadata_path(&target_file_path);
-            fs::write(
-                &storage_metadata_path,
-                serde_json::to_string(&storage_metadata.0)
-                    .context("Failed to serialize storage metadata as json")?,
-            )
+            self.write_storage_metadata(&target_file_path, &storage_metadata)
+                .await?;
+        }
+
+        debug!(bytes = bytes_read, "upload completed");
+
+        Ok(())
+    }
+
+    /// Starts a streaming upload of unknown total size, mirroring the multipart upload API
+    /// that arrow-rs `object_store` exposes through `put_multipart`. The returned writer can be
+    /// fed arbitrary-length chunks; the upload only becomes visible at `to` once
+    /// `complete_multipart_upload` is called, and is rolled back by `abort_multipart_upload`.
+    async fn start_multipart_upload(
+        &self,
+        to: &RemotePath,
+    ) -> anyhow::Result<(MultipartUploadId, Pin<Box<dyn io::AsyncWrite + Send + Sync>>)> {
+        let (upload_id, temp_file_path) = self.multipart_temp_path(to);
+        create_target_directory(&temp_file_path).await?;
+
+        let destination = io::BufWriter::new(
+            fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&temp_file_path)
+                .await
+                .with_context(|| {
+                    format!("Failed to open multipart temp destination at '{temp_file_path}'")
+                })?,
+        );
+
+        Ok((upload_id, Box::pin(destination)))
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        to: &RemotePath,
+        upload_id: &MultipartUploadId,
+    ) -> anyhow::Result<()> {
+        let target_file_path = to.with_base(&self.storage_root);
+        let temp_file_path = path_with_suffix_extension(
+            &target_file_path,
+            &format!("{LOCAL_FS_TEMP_FILE_SUFFIX}-{upload_id}"),
+        );
+
+        // Same durable-rename dance as `upload`: this makes the multipart upload visible
+        // atomically, so readers never observe a partially written object.
+        fs::rename(&temp_file_path, &target_file_path)
             .await
             .with_context(|| {
                 format!(
-                    "Failed to write metadata to the local storage at '{storage_metadata_path}'",
+                    "Failed to complete multipart upload (rename) to '{target_file_path}', upload_id={upload_id}",
                 )
             })?;
-        }
 
         Ok(())
     }
 
+    async fn abort_multipart_upload(
+        &self,
+        to: &RemotePath,
+        upload_id: &MultipartUploadId,
+    ) -> anyhow::Result<()> {
+        let target_file_path = to.with_base(&self.storage_root);
+        let temp_file_path = path_with_suffix_extension(
+            &target_file_path,
+            &format!("{LOCAL_FS_TEMP_FILE_SUFFIX}-{upload_id}"),
+        );
+        match fs::remove_file(&temp_file_path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(anyhow::anyhow!(e)),
+        }
+    }
+
+    /// Returns size, last-modified time, and the `StorageMetadata` sidecar (if any) for `path`
+    /// without downloading its contents, letting upper layers size-check, cache-validate, and
+    /// read back custom metadata for layers cheaply.
+    #[tracing::instrument(skip(self), fields(path = %path))]
+    async fn head_object(&self, path: &RemotePath) -> Result<ObjectMetadata, DownloadError> {
+        let target_path = path.with_base(&self.storage_root);
+        let file_metadata = fs::metadata(&target_path).await.map_err(|e| {
+            if e.kind() == ErrorKind::NotFound {
+                DownloadError::NotFound
+            } else {
+                DownloadError::Other(anyhow::Error::new(e).context(format!(
+                    "Failed to stat file {target_path:?} for head_object"
+                )))
+            }
+        })?;
+        let metadata = self
+            .read_storage_metadata(&target_path)
+            .await
+            .map_err(DownloadError::Other)?;
+
+        Ok(ObjectMetadata {
+            size: file_metadata.len(),
+            last_modified: file_metadata
+                .modified()
+                .map_err(|e| DownloadError::Other(e.into()))?,
+            metadata,
+        })
+    }
+
+    #[tracing::instrument(skip(self), fields(path = %from))]
     async fn download(&self, from: &RemotePath) -> Result<Download, DownloadError> {
         let target_path = from.with_base(&self.storage_root);
         if file_exists(&target_path).map_err(DownloadError::BadInput)? {
@@ -338,6 +845,7 @@ impl RemoteStorage for LocalFs {
         }
     }
 
+    #[tracing::instrument(skip(self), fields(path = %from, start_inclusive, end_exclusive = ?end_exclusive))]
     async fn download_byte_range(
         &self,
         from: &RemotePath,
@@ -354,6 +862,13 @@ impl RemoteStorage for LocalFs {
         }
         let target_path = from.with_base(&self.storage_root);
         if file_exists(&target_path).map_err(DownloadError::BadInput)? {
+            let object_size = self.head_object(from).await?.size;
+            if start_inclusive > object_size {
+                return Err(DownloadError::Other(anyhow::anyhow!(
+                    "Invalid range, start ({start_inclusive}) is past the end of the object ({object_size} bytes)"
+                )));
+            }
+
             let mut source = tokio::fs::OpenOptions::new()
                 .read(true)
                 .open(&target_path)
@@ -389,6 +904,7 @@ impl RemoteStorage for LocalFs {
         }
     }
 
+    #[tracing::instrument(skip(self), fields(path = %path))]
     async fn delete(&self, path: &RemotePath) -> anyhow::Result<()> {
         let file_path = path.with_base(&self.storage_root);
         match fs::remove_file(&file_path).await {
@@ -407,6 +923,136 @@ impl RemoteStorage for LocalFs {
         }
         Ok(())
     }
+
+    /// Copies `from` to `to`, including the sidecar `.metadata` file if one exists.
+    /// Uses the same temp-file-and-rename dance as `upload` so that a concurrent reader of
+    /// `to` never observes a partially written copy.
+    #[tracing::instrument(skip(self), fields(from = %from, to = %to))]
+    async fn copy(&self, from: &RemotePath, to: &RemotePath) -> anyhow::Result<()> {
+        let from_path = from.with_base(&self.storage_root);
+        let to_path = to.with_base(&self.storage_root);
+        create_target_directory(&to_path).await?;
+
+        let temp_file_path = self.copy_temp_path(&to_path);
+        fs::copy(&from_path, &temp_file_path)
+            .await
+            .with_context(|| format!("Failed to copy '{from_path}' to temp file '{temp_file_path}'"))?;
+        fs::rename(&temp_file_path, &to_path)
+            .await
+            .with_context(|| format!("Failed to copy (rename) to '{to_path}'"))?;
+
+        let from_metadata_path = storage_metadata_path(&from_path);
+        if from_metadata_path.exists() {
+            let to_metadata_path = storage_metadata_path(&to_path);
+            fs::copy(&from_metadata_path, &to_metadata_path)
+                .await
+                .with_context(|| {
+                    format!("Failed to copy metadata sidecar to '{to_metadata_path}'")
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Like `copy`, but fails if `to` already exists, guaranteeing atomic no-clobber semantics
+    /// for concurrent callers racing to create the same destination. Hard-links `from` straight
+    /// into `to` rather than copying through a temp file: `from` and `to` always live under the
+    /// same `storage_root`, so the link is atomic and never exposes a partially-written
+    /// destination, and it's safe to share the inode because objects are only ever replaced via
+    /// `upload`'s write-temp-then-rename dance, never mutated in place. This also means a losing
+    /// racer never has to materialize a (possibly multi-GB) source it's just going to discard.
+    async fn copy_if_not_exists(&self, from: &RemotePath, to: &RemotePath) -> anyhow::Result<()> {
+        let from_path = from.with_base(&self.storage_root);
+        let to_path = to.with_base(&self.storage_root);
+        create_target_directory(&to_path).await?;
+
+        match fs::hard_link(&from_path, &to_path).await {
+            Ok(()) => {}
+            // Only AlreadyExists means "destination already taken"; any other error (e.g. the
+            // source not existing, or a permissions failure) should be reported as what it is.
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                bail!("Destination '{to_path}' already exists")
+            }
+            Err(e) => {
+                return Err(anyhow::Error::new(e)
+                    .context(format!("Failed to link '{from_path}' to '{to_path}'")))
+            }
+        }
+
+        let from_metadata_path = storage_metadata_path(&from_path);
+        if from_metadata_path.exists() {
+            let to_metadata_path = storage_metadata_path(&to_path);
+            fs::copy(&from_metadata_path, &to_metadata_path)
+                .await
+                .with_context(|| {
+                    format!("Failed to copy metadata sidecar to '{to_metadata_path}'")
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+// Wraps `inner` so each chunk is hashed and re-emitted as soon as it arrives, and the running
+// digest is only checked against `expected_digest` once `inner` is exhausted: a mismatch is
+// reported as one final `Err` item rather than an up-front `Result`, since corruption can't be
+// known until the last byte has streamed past.
+fn verify_checksum_stream(
+    inner: Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Sync>>,
+    algorithm: ChecksumAlgorithm,
+    expected_digest: String,
+    path: RemotePath,
+) -> Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Sync>> {
+    enum State {
+        Streaming {
+            inner: Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Sync>>,
+            hasher: ChecksumHasher,
+        },
+        Done,
+    }
+
+    Box::pin(futures::stream::unfold(
+        State::Streaming {
+            inner,
+            hasher: ChecksumHasher::new(algorithm),
+        },
+        move |state| {
+            let expected_digest = expected_digest.clone();
+            let path = path.clone();
+            async move {
+                match state {
+                    State::Streaming { mut inner, mut hasher } => match inner.next().await {
+                        Some(Ok(chunk)) => {
+                            hasher.update(&chunk);
+                            Some((Ok(chunk), State::Streaming { inner, hasher }))
+                        }
+                        Some(Err(e)) => Some((Err(e), State::Streaming { inner, hasher })),
+                        None => {
+                            let actual_digest = hasher.finish();
+                            if actual_digest == expected_digest {
+                                None
+                            } else {
+                                let message = format!(
+                                    "Checksum mismatch downloading '{path}': expected {expected_digest}, got {actual_digest}"
+                                );
+                                Some((
+                                    Err(std::io::Error::new(std::io::ErrorKind::InvalidData, message)),
+                                    State::Done,
+                                ))
+                            }
+                        }
+                    },
+                    State::Done => None,
+                }
+            }
+        },
+    ))
+}
+
+// takes up to `n` items off the front of `items`, leaving the rest for the next page
+fn drain_prefix<T>(items: &mut Vec<T>, n: usize) -> Vec<T> {
+    let n = n.min(items.len());
+    items.drain(..n).collect()
 }
 
 fn storage_metadata_path(original_path: &Utf8Path) -> Utf8PathBuf {
@@ -679,6 +1325,173 @@ mod fs_tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn multipart_upload_handle_streams_chunks() -> anyhow::Result<()> {
+        let storage = create_storage()?;
+        let target = RemotePath::new(Utf8Path::new("timelines/some_timeline/multipart_chunks"))?;
+
+        let mut upload = MultipartUpload::new(&storage, target.clone()).await?;
+        upload.write_part(Bytes::from_static(b"chunk one, ")).await?;
+        upload.write_part(Bytes::from_static(b"chunk two")).await?;
+        upload.complete().await?;
+
+        let contents = read_and_assert_remote_file_contents(&storage, &target, None).await?;
+        assert_eq!(contents, "chunk one, chunk two");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn multipart_upload_completes() -> anyhow::Result<()> {
+        let storage = create_storage()?;
+        let target = RemotePath::new(Utf8Path::new("timelines/some_timeline/multipart"))?;
+
+        let (upload_id, mut writer) = storage.start_multipart_upload(&target).await?;
+        writer.write_all(b"hello ").await?;
+        writer.write_all(b"world").await?;
+        writer.flush().await?;
+        drop(writer);
+
+        storage
+            .complete_multipart_upload(&target, &upload_id)
+            .await?;
+
+        let contents = read_and_assert_remote_file_contents(&storage, &target, None).await?;
+        assert_eq!(contents, "hello world");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn multipart_upload_abort_removes_temp_file() -> anyhow::Result<()> {
+        let storage = create_storage()?;
+        let target = RemotePath::new(Utf8Path::new("timelines/some_timeline/multipart"))?;
+
+        let (upload_id, mut writer) = storage.start_multipart_upload(&target).await?;
+        writer.write_all(b"partial").await?;
+        writer.flush().await?;
+        drop(writer);
+
+        storage.abort_multipart_upload(&target, &upload_id).await?;
+
+        assert!(storage.list_all().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn checksum_roundtrip_and_mismatch() -> anyhow::Result<()> {
+        let storage = create_storage()?;
+        let target = RemotePath::new(Utf8Path::new("timelines/some_timeline/checksummed"))?;
+        let contents = Bytes::from_static(b"important layer bytes");
+
+        storage
+            .upload_with_checksum(
+                futures::stream::once(futures::future::ready(Ok(contents.clone()))),
+                contents.len(),
+                &target,
+                None,
+                ChecksumAlgorithm::Crc32c,
+            )
+            .await?;
+
+        let verified = storage.download_verifying_checksum(&target).await?;
+        assert_eq!(aggregate(verified.download_stream).await?, contents);
+
+        // corrupt the file on disk and make sure the checksum catches it. The mismatch can only
+        // be known once the whole stream has been read, so it surfaces as an error on the stream
+        // itself rather than from `download_verifying_checksum` up front.
+        let file_path = target.with_base(&storage.storage_root);
+        std::fs::write(&file_path, b"corrupted bytes!!!!!!")?;
+
+        let corrupted = storage.download_verifying_checksum(&target).await?;
+        match aggregate(corrupted.download_stream).await {
+            Err(e) => assert!(e.to_string().contains("Checksum mismatch")),
+            Ok(bytes) => panic!("Expected a checksum mismatch error, got bytes: {bytes:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn head_object_returns_size() -> anyhow::Result<()> {
+        let storage = create_storage()?;
+        let upload_name = "upload_1";
+        let upload_target = upload_dummy_file(&storage, upload_name, None).await?;
+
+        let object_metadata = storage.head_object(&upload_target).await?;
+        assert_eq!(
+            object_metadata.size,
+            dummy_contents(upload_name).len() as u64
+        );
+
+        let non_existing_path = RemotePath::new(Utf8Path::new("somewhere/else"))?;
+        match storage.head_object(&non_existing_path).await {
+            Err(DownloadError::NotFound) => {}
+            other => panic!("Should get a NotFound error for a missing object, but got: {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn copy_file_with_metadata() -> anyhow::Result<()> {
+        let storage = create_storage()?;
+        let upload_name = "upload_1";
+        let metadata = StorageMetadata(HashMap::from([("one".to_string(), "1".to_string())]));
+        let upload_target =
+            upload_dummy_file(&storage, upload_name, Some(metadata.clone())).await?;
+
+        let copy_target = RemotePath::new(Utf8Path::new("timelines/some_timeline/copy_1"))?;
+        storage.copy(&upload_target, &copy_target).await?;
+
+        let copied_contents =
+            read_and_assert_remote_file_contents(&storage, &copy_target, Some(&metadata)).await?;
+        assert_eq!(dummy_contents(upload_name), copied_contents);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rename_moves_file_and_metadata() -> anyhow::Result<()> {
+        let storage = create_storage()?;
+        let upload_name = "upload_1";
+        let metadata = StorageMetadata(HashMap::from([("one".to_string(), "1".to_string())]));
+        let upload_target =
+            upload_dummy_file(&storage, upload_name, Some(metadata.clone())).await?;
+
+        let renamed_target = RemotePath::new(Utf8Path::new("timelines/some_timeline/renamed"))?;
+        storage.rename(&upload_target, &renamed_target).await?;
+
+        assert_eq!(storage.list_all().await?, vec![renamed_target.clone()]);
+
+        let contents =
+            read_and_assert_remote_file_contents(&storage, &renamed_target, Some(&metadata))
+                .await?;
+        assert_eq!(dummy_contents(upload_name), contents);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn copy_if_not_exists_does_not_clobber() -> anyhow::Result<()> {
+        let storage = create_storage()?;
+        let upload_name = "upload_1";
+        let upload_target = upload_dummy_file(&storage, upload_name, None).await?;
+
+        let copy_target = RemotePath::new(Utf8Path::new("timelines/some_timeline/copy_1"))?;
+        storage
+            .copy_if_not_exists(&upload_target, &copy_target)
+            .await?;
+
+        storage
+            .copy_if_not_exists(&upload_target, &copy_target)
+            .await
+            .expect_err("copy_if_not_exists should fail when destination already exists");
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn delete_file() -> anyhow::Result<()> {
         let storage = create_storage()?;
@@ -730,10 +1543,32 @@ mod fs_tests {
 
         assert_eq!(
             partial_download_with_metadata.metadata,
-            Some(metadata),
+            Some(metadata.clone()),
             "We should get the same metadata back for partial download"
         );
 
+        let head = storage.head_object(&upload_target).await?;
+        assert_eq!(head.size, dummy_contents(upload_name).len() as u64);
+        assert_eq!(head.metadata, Some(metadata));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn list_streaming_pages_match_list() -> anyhow::Result<()> {
+        let storage = create_storage()?;
+        let child = upload_dummy_file(&storage, "grandparent/parent/child", None).await?;
+        let uncle = upload_dummy_file(&storage, "grandparent/uncle", None).await?;
+
+        let mut pages = std::pin::pin!(storage.list_streaming(None, ListingMode::NoDelimiter));
+        let mut keys = Vec::new();
+        while let Some(page) = pages.next().await {
+            let page = page?;
+            assert!(page.prefixes.is_empty());
+            keys.extend(page.keys);
+        }
+        assert_eq!(keys, [uncle.clone(), child.clone()].to_vec());
+
         Ok(())
     }
 
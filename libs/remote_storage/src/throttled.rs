@@ -0,0 +1,171 @@
+//! A composable decorator that injects configurable latency and bandwidth limits around an
+//! arbitrary `RemoteStorage`, following the throttled-store pattern used to simulate
+//! cloud-provider behavior in object-store test suites. This lets upload/download retry logic
+//! and timeout handling be exercised deterministically against slow/stalled backends without
+//! needing real S3.
+
+use std::pin::Pin;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::stream::{Stream, StreamExt};
+use tokio::io;
+use tokio::time::sleep;
+
+use crate::local_fs::{MultipartUploadId, ObjectMetadata};
+use crate::{Download, DownloadError, Listing, ListingMode, RemotePath};
+
+use super::{RemoteStorage, StorageMetadata};
+
+/// Per-operation delays applied by `ThrottledStorage`. A zero `Duration` disables that knob.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThrottleConfig {
+    pub list_latency: Duration,
+    pub head_latency: Duration,
+    pub delete_latency: Duration,
+    pub upload_latency: Duration,
+    pub download_latency: Duration,
+    /// Extra delay applied per byte yielded from `download_byte_range`'s stream, so partial and
+    /// ranged downloads are throttled too, not just the initial request.
+    pub download_byte_delay: Duration,
+}
+
+pub struct ThrottledStorage<S> {
+    inner: S,
+    config: ThrottleConfig,
+}
+
+impl<S> ThrottledStorage<S> {
+    pub fn new(inner: S, config: ThrottleConfig) -> Self {
+        Self { inner, config }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: RemoteStorage> RemoteStorage for ThrottledStorage<S> {
+    async fn list(
+        &self,
+        prefix: Option<&RemotePath>,
+        mode: ListingMode,
+    ) -> Result<Listing, DownloadError> {
+        maybe_sleep(self.config.list_latency).await;
+        self.inner.list(prefix, mode).await
+    }
+
+    fn list_streaming<'a>(
+        &'a self,
+        prefix: Option<&'a RemotePath>,
+        mode: ListingMode,
+    ) -> Pin<Box<dyn Stream<Item = Result<Listing, DownloadError>> + Send + 'a>> {
+        let list_latency = self.config.list_latency;
+        Box::pin(futures::stream::once(maybe_sleep(list_latency)).flat_map(move |()| {
+            self.inner.list_streaming(prefix, mode)
+        }))
+    }
+
+    async fn upload(
+        &self,
+        data: impl Stream<Item = std::io::Result<Bytes>> + Send + Sync,
+        data_size_bytes: usize,
+        to: &RemotePath,
+        metadata: Option<StorageMetadata>,
+    ) -> anyhow::Result<()> {
+        maybe_sleep(self.config.upload_latency).await;
+        self.inner.upload(data, data_size_bytes, to, metadata).await
+    }
+
+    async fn start_multipart_upload(
+        &self,
+        to: &RemotePath,
+    ) -> anyhow::Result<(MultipartUploadId, Pin<Box<dyn io::AsyncWrite + Send + Sync>>)> {
+        maybe_sleep(self.config.upload_latency).await;
+        self.inner.start_multipart_upload(to).await
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        to: &RemotePath,
+        upload_id: &MultipartUploadId,
+    ) -> anyhow::Result<()> {
+        maybe_sleep(self.config.upload_latency).await;
+        self.inner.complete_multipart_upload(to, upload_id).await
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        to: &RemotePath,
+        upload_id: &MultipartUploadId,
+    ) -> anyhow::Result<()> {
+        maybe_sleep(self.config.upload_latency).await;
+        self.inner.abort_multipart_upload(to, upload_id).await
+    }
+
+    async fn download(&self, from: &RemotePath) -> Result<Download, DownloadError> {
+        maybe_sleep(self.config.download_latency).await;
+        let download = self.inner.download(from).await?;
+        Ok(throttle_download(download, self.config.download_byte_delay))
+    }
+
+    async fn download_byte_range(
+        &self,
+        from: &RemotePath,
+        start_inclusive: u64,
+        end_exclusive: Option<u64>,
+    ) -> Result<Download, DownloadError> {
+        maybe_sleep(self.config.download_latency).await;
+        let download = self
+            .inner
+            .download_byte_range(from, start_inclusive, end_exclusive)
+            .await?;
+        Ok(throttle_download(download, self.config.download_byte_delay))
+    }
+
+    async fn delete(&self, path: &RemotePath) -> anyhow::Result<()> {
+        maybe_sleep(self.config.delete_latency).await;
+        self.inner.delete(path).await
+    }
+
+    async fn delete_objects<'a>(&self, paths: &'a [RemotePath]) -> anyhow::Result<()> {
+        maybe_sleep(self.config.delete_latency).await;
+        self.inner.delete_objects(paths).await
+    }
+
+    async fn head_object(&self, path: &RemotePath) -> Result<ObjectMetadata, DownloadError> {
+        maybe_sleep(self.config.head_latency).await;
+        self.inner.head_object(path).await
+    }
+
+    async fn copy(&self, from: &RemotePath, to: &RemotePath) -> anyhow::Result<()> {
+        maybe_sleep(self.config.upload_latency).await;
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &RemotePath, to: &RemotePath) -> anyhow::Result<()> {
+        maybe_sleep(self.config.upload_latency).await;
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+async fn maybe_sleep(duration: Duration) {
+    if !duration.is_zero() {
+        sleep(duration).await;
+    }
+}
+
+fn throttle_download(download: Download, per_byte_delay: Duration) -> Download {
+    if per_byte_delay.is_zero() {
+        return download;
+    }
+
+    let throttled = download.download_stream.then(move |chunk| async move {
+        if let Ok(bytes) = &chunk {
+            sleep(per_byte_delay.saturating_mul(bytes.len() as u32)).await;
+        }
+        chunk
+    });
+
+    Download {
+        metadata: download.metadata,
+        download_stream: Box::pin(throttled) as Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Sync>>,
+    }
+}
@@ -10,6 +10,8 @@ use crate::bindings::uint32;
 use crate::bindings::walproposer_api;
 use crate::bindings::PGAsyncReadResult;
 use crate::bindings::PGAsyncWriteResult;
+use crate::bindings::PG_ASYNC_READ_FAIL;
+use crate::bindings::PG_ASYNC_WRITE_FAIL;
 use crate::bindings::Safekeeper;
 use crate::bindings::Size;
 use crate::bindings::StringInfoData;
@@ -20,110 +22,145 @@ use crate::bindings::WalProposerConnStatusType;
 use crate::bindings::WalProposerConnectPollStatusType;
 use crate::bindings::WalProposerExecStatusType;
 use crate::bindings::WalproposerShmemState;
+use crate::bindings::WP_CONNECTION_BAD;
+use crate::bindings::WP_CONN_POLLING_FAILED;
+use crate::bindings::WP_EXEC_FAILED;
 use crate::bindings::XLogRecPtr;
 use crate::walproposer::ApiImpl;
 use crate::walproposer::WaitResult;
 
+/// Safety net around every callback below: a panic unwinding across the FFI boundary into C
+/// walproposer code is undefined behavior, so we catch it here, route the message through
+/// `ApiImpl::log_internal` at `Level::Panic`, and return the caller-supplied `$sentinel` for the
+/// callback's return type instead of letting the unwind continue. Each call site picks its own
+/// sentinel rather than relying on `Default::default()`: that blanket sentinel doesn't compile for
+/// the pointer-returning callbacks (raw pointers have no `Default`), and for the C-enum-as-`u32`
+/// result/status types it silently returns the *success* variant (whatever value happens to sit at
+/// `0`), which tells the caller a panicked operation actually worked. This also covers each
+/// callback's own `.unwrap()` calls (e.g. `conn_send_query` on `query.to_str()`, `log_internal` on
+/// the line conversion), which previously could panic and take the whole postgres process down
+/// with them.
+macro_rules! callback {
+    ($wp:expr, $sentinel:expr, $body:expr) => {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| $body)) {
+            Ok(result) => result,
+            Err(panic) => {
+                report_callback_panic($wp, panic);
+                $sentinel
+            }
+        }
+    };
+}
+
 extern "C" fn get_shmem_state(wp: *mut WalProposer) -> *mut WalproposerShmemState {
-    unsafe {
+    callback!(wp, std::ptr::null_mut(), unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).get_shmem_state()
-    }
+    })
 }
 
 extern "C" fn start_streaming(wp: *mut WalProposer, startpos: XLogRecPtr) {
-    unsafe {
+    callback!(wp, (), unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).start_streaming(startpos)
-    }
+    })
 }
 
 extern "C" fn get_flush_rec_ptr(wp: *mut WalProposer) -> XLogRecPtr {
-    unsafe {
+    callback!(wp, 0, unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).get_flush_rec_ptr()
-    }
+    })
 }
 
 extern "C" fn get_current_timestamp(wp: *mut WalProposer) -> TimestampTz {
-    unsafe {
+    callback!(wp, 0, unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).get_current_timestamp()
-    }
+    })
 }
 
 extern "C" fn conn_error_message(sk: *mut Safekeeper) -> *mut ::std::os::raw::c_char {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, std::ptr::null_mut(), unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         let msg = (*api).conn_error_message(&mut (*sk));
-        let msg = CString::new(msg).unwrap();
-        // TODO: fix leaking error message
-        msg.into_raw()
-    }
+
+        // Parked in sk.errmsg and reused on the next call, the same way conn_async_read reuses
+        // sk.inbuf: the returned pointer only needs to stay valid until we're called again.
+        store_c_string(&mut (*sk).errmsg, &msg)
+    })
 }
 
 extern "C" fn conn_status(sk: *mut Safekeeper) -> WalProposerConnStatusType {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, WP_CONNECTION_BAD, unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).conn_status(&mut (*sk))
-    }
+    })
 }
 
 extern "C" fn conn_connect_start(sk: *mut Safekeeper) {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, (), unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).conn_connect_start(&mut (*sk))
-    }
+    })
 }
 
 extern "C" fn conn_connect_poll(sk: *mut Safekeeper) -> WalProposerConnectPollStatusType {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, WP_CONN_POLLING_FAILED, unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).conn_connect_poll(&mut (*sk))
-    }
+    })
 }
 
 extern "C" fn conn_send_query(sk: *mut Safekeeper, query: *mut ::std::os::raw::c_char) -> bool {
-    let query = unsafe { CStr::from_ptr(query) };
-    let query = query.to_str().unwrap();
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, false, unsafe {
+        let query = CStr::from_ptr(query);
+        let query = query.to_str().unwrap();
 
-    unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).conn_send_query(&mut (*sk), query)
-    }
+    })
 }
 
 extern "C" fn conn_get_query_result(sk: *mut Safekeeper) -> WalProposerExecStatusType {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, WP_EXEC_FAILED, unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).conn_get_query_result(&mut (*sk))
-    }
+    })
 }
 
 extern "C" fn conn_flush(sk: *mut Safekeeper) -> ::std::os::raw::c_int {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, -1, unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).conn_flush(&mut (*sk))
-    }
+    })
 }
 
 extern "C" fn conn_finish(sk: *mut Safekeeper) {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, (), unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).conn_finish(&mut (*sk))
-    }
+    })
 }
 
 extern "C" fn conn_async_read(
@@ -131,7 +168,8 @@ extern "C" fn conn_async_read(
     buf: *mut *mut ::std::os::raw::c_char,
     amount: *mut ::std::os::raw::c_int,
 ) -> PGAsyncReadResult {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, PG_ASYNC_READ_FAIL, unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         let (res, result) = (*api).conn_async_read(&mut (*sk));
@@ -149,7 +187,7 @@ extern "C" fn conn_async_read(
         *amount = res.len() as i32;
 
         result
-    }
+    })
 }
 
 extern "C" fn conn_async_write(
@@ -157,12 +195,13 @@ extern "C" fn conn_async_write(
     buf: *const ::std::os::raw::c_void,
     size: usize,
 ) -> PGAsyncWriteResult {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, PG_ASYNC_WRITE_FAIL, unsafe {
         let buf = std::slice::from_raw_parts(buf as *const u8, size);
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).conn_async_write(&mut (*sk), buf)
-    }
+    })
 }
 
 extern "C" fn conn_blocking_write(
@@ -170,12 +209,13 @@ extern "C" fn conn_blocking_write(
     buf: *const ::std::os::raw::c_void,
     size: usize,
 ) -> bool {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, false, unsafe {
         let buf = std::slice::from_raw_parts(buf as *const u8, size);
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).conn_blocking_write(&mut (*sk), buf)
-    }
+    })
 }
 
 extern "C" fn recovery_download(
@@ -184,11 +224,12 @@ extern "C" fn recovery_download(
     startpos: XLogRecPtr,
     endpos: XLogRecPtr,
 ) -> bool {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, false, unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).recovery_download(&mut (*sk), startpos, endpos)
-    }
+    })
 }
 
 #[allow(clippy::unnecessary_cast)]
@@ -198,52 +239,56 @@ extern "C" fn wal_read(
     startptr: XLogRecPtr,
     count: Size,
 ) {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, (), unsafe {
         let buf = std::slice::from_raw_parts_mut(buf as *mut u8, count);
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).wal_read(&mut (*sk), buf, startptr)
-    }
+    })
 }
 
 extern "C" fn wal_reader_allocate(sk: *mut Safekeeper) {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, (), unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).wal_reader_allocate(&mut (*sk));
-    }
+    })
 }
 
 extern "C" fn free_event_set(wp: *mut WalProposer) {
-    unsafe {
+    callback!(wp, (), unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).free_event_set(&mut (*wp));
-    }
+    })
 }
 
 extern "C" fn init_event_set(wp: *mut WalProposer) {
-    unsafe {
+    callback!(wp, (), unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).init_event_set(&mut (*wp));
-    }
+    })
 }
 
 extern "C" fn update_event_set(sk: *mut Safekeeper, events: uint32) {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, (), unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).update_event_set(&mut (*sk), events);
-    }
+    })
 }
 
 extern "C" fn add_safekeeper_event_set(sk: *mut Safekeeper, events: uint32) {
-    unsafe {
+    let wp = unsafe { (*sk).wp };
+    callback!(wp, (), unsafe {
         let callback_data = (*(*(*sk).wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).add_safekeeper_event_set(&mut (*sk), events);
-    }
+    })
 }
 
 extern "C" fn wait_event_set(
@@ -252,7 +297,7 @@ extern "C" fn wait_event_set(
     event_sk: *mut *mut Safekeeper,
     events: *mut uint32,
 ) -> ::std::os::raw::c_int {
-    unsafe {
+    callback!(wp, 0, unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         let result = (*api).wait_event_set(&mut (*wp), timeout);
@@ -273,7 +318,7 @@ extern "C" fn wait_event_set(
                 1
             }
         }
-    }
+    })
 }
 
 extern "C" fn strong_random(
@@ -281,44 +326,44 @@ extern "C" fn strong_random(
     buf: *mut ::std::os::raw::c_void,
     len: usize,
 ) -> bool {
-    unsafe {
+    callback!(wp, false, unsafe {
         let buf = std::slice::from_raw_parts_mut(buf as *mut u8, len);
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).strong_random(buf)
-    }
+    })
 }
 
 extern "C" fn get_redo_start_lsn(wp: *mut WalProposer) -> XLogRecPtr {
-    unsafe {
+    callback!(wp, 0, unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).get_redo_start_lsn()
-    }
+    })
 }
 
 extern "C" fn finish_sync_safekeepers(wp: *mut WalProposer, lsn: XLogRecPtr) {
-    unsafe {
+    callback!(wp, (), unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).finish_sync_safekeepers(lsn)
-    }
+    })
 }
 
 extern "C" fn process_safekeeper_feedback(wp: *mut WalProposer, commit_lsn: XLogRecPtr) {
-    unsafe {
+    callback!(wp, (), unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).process_safekeeper_feedback(&mut (*wp), commit_lsn)
-    }
+    })
 }
 
 extern "C" fn confirm_wal_streamed(wp: *mut WalProposer, lsn: XLogRecPtr) {
-    unsafe {
+    callback!(wp, (), unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).confirm_wal_streamed(&mut (*wp), lsn)
-    }
+    })
 }
 
 extern "C" fn log_internal(
@@ -326,24 +371,48 @@ extern "C" fn log_internal(
     level: ::std::os::raw::c_int,
     line: *const ::std::os::raw::c_char,
 ) {
-    unsafe {
+    callback!(wp, (), unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         let line = CStr::from_ptr(line);
         let line = line.to_str().unwrap();
-        (*api).log_internal(&mut (*wp), Level::from(level as u32), line)
-    }
+        let level = Level::from(level as u32).unwrap_or(Level::Log);
+        (*api).log_internal(&mut (*wp), level, line)
+    })
 }
 
 extern "C" fn after_election(wp: *mut WalProposer) {
-    unsafe {
+    callback!(wp, (), unsafe {
         let callback_data = (*(*wp).config).callback_data;
         let api = callback_data as *mut Box<dyn ApiImpl>;
         (*api).after_election(&mut (*wp))
+    })
+}
+
+/// Invoked by the `callback!` macro when a callback body panics instead of returning normally.
+/// Routes the panic message through `ApiImpl::log_internal` at `Level::Panic` so operators see it
+/// in their usual logs, then lets the macro return its call site's sentinel in place of unwinding
+/// across the FFI boundary into C.
+fn report_callback_panic(wp: *mut WalProposer, panic: Box<dyn std::any::Any + Send>) {
+    let message = panic_message(&panic);
+    unsafe {
+        let callback_data = (*(*wp).config).callback_data;
+        let api = callback_data as *mut Box<dyn ApiImpl>;
+        (*api).log_internal(&mut (*wp), Level::Panic, &message);
     }
 }
 
-#[derive(Debug)]
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = panic.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "walproposer callback panicked with a non-string payload".to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Level {
     Debug5,
     Debug4,
@@ -361,10 +430,10 @@ pub enum Level {
 }
 
 impl Level {
-    pub fn from(elevel: u32) -> Level {
+    pub fn from(elevel: u32) -> Result<Level, UnknownLogLevel> {
         use crate::bindings::*;
 
-        match elevel {
+        Ok(match elevel {
             DEBUG5 => Level::Debug5,
             DEBUG4 => Level::Debug4,
             DEBUG3 => Level::Debug3,
@@ -378,8 +447,44 @@ impl Level {
             FATAL => Level::Fatal,
             PANIC => Level::Panic,
             WPEVENT => Level::WPEvent,
-            _ => panic!("unknown log level {}", elevel),
+            _ => return Err(UnknownLogLevel(elevel)),
+        })
+    }
+}
+
+/// Returned by `Level::from` when Postgres passes an `elevel` this crate doesn't recognize, e.g.
+/// a log level added by a newer Postgres than this shim was built against. Kept as a recoverable
+/// error rather than a `panic!` so an unrecognized elevel crossing the FFI boundary can't take the
+/// whole process down.
+#[derive(Debug)]
+pub struct UnknownLogLevel(pub u32);
+
+impl std::fmt::Display for UnknownLogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "unknown log level {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownLogLevel {}
+
+/// Opt-in bridge from postgres log levels into `tracing`. Not wired into `log_internal`
+/// automatically, since most `ApiImpl` implementations already route these lines through
+/// postgres's own logging; an implementation that wants WAL-proposer activity correlated in a
+/// `tracing`-based observability pipeline can call this from its own `log_internal` method.
+pub fn log_to_tracing(wp: *mut WalProposer, level: Level, line: &str) {
+    // SAFETY: callers only get a `Level`/line pair by already being inside a `log_internal`
+    // callback, which guarantees `wp` is a valid, non-null WalProposer for the duration of the call.
+    let (term, lsn) = unsafe { ((*wp).propTerm as u64, (*wp).truncateLsn) };
+    let lsn = format!("{lsn:X}");
+
+    match level {
+        Level::Debug5 | Level::Debug4 | Level::Debug3 => tracing::trace!(term, lsn, "{line}"),
+        Level::Debug2 | Level::Debug1 => tracing::debug!(term, lsn, "{line}"),
+        Level::Log | Level::Info | Level::Notice | Level::WPEvent => {
+            tracing::info!(term, lsn, "{line}")
         }
+        Level::Warning => tracing::warn!(term, lsn, "{line}"),
+        Level::Error | Level::Fatal | Level::Panic => tracing::error!(term, lsn, "{line}"),
     }
 }
 
@@ -424,6 +529,22 @@ impl std::fmt::Display for Level {
     }
 }
 
+/// Stores `message` as a NUL-terminated C string in `slot`, freeing or reusing whatever buffer
+/// was previously parked there, and returns a pointer that stays valid until the next call
+/// through this same `slot`. Generalizes the `take_vec_u8`/`store_vec_u8` reuse pattern that
+/// `conn_async_read` already relies on for `sk.inbuf`, so every callback that must hand owned
+/// memory back to C can share one audited implementation instead of reinventing it (and, unlike
+/// the old `conn_error_message`, without leaking an allocation on every call).
+pub(crate) fn store_c_string(slot: &mut StringInfoData, message: &str) -> *mut ::std::os::raw::c_char {
+    let message = CString::new(message).unwrap_or_else(|_| CString::new("<error message contained a NUL byte>").unwrap());
+
+    let mut buf = take_vec_u8(slot).unwrap_or_default();
+    buf.clear();
+    buf.extend_from_slice(message.as_bytes_with_nul());
+
+    store_vec_u8(slot, buf)
+}
+
 /// Take ownership of `Vec<u8>` from StringInfoData.
 #[allow(clippy::unnecessary_cast)]
 pub(crate) fn take_vec_u8(pg: &mut StringInfoData) -> Option<Vec<u8>> {
@@ -0,0 +1,193 @@
+//! A ready-made `mio`-backed implementation of the event-set portion of `ApiImpl`
+//! (`init_event_set` / `update_event_set` / `add_safekeeper_event_set` / `wait_event_set` /
+//! `free_event_set`). Embedders that would otherwise hand-roll epoll bookkeeping and the
+//! fd<->`Safekeeper` mapping can instead keep one `EventSet` per `WalProposer` and let it
+//! implement those five callbacks directly, rather than writing their own glue on top of it.
+
+use std::collections::HashMap;
+use std::os::unix::io::RawFd;
+use std::sync::Arc;
+use std::time::Duration;
+
+use mio::unix::SourceFd;
+use mio::{Events, Interest, Poll, Token, Waker};
+
+use crate::bindings::uint32;
+use crate::bindings::Safekeeper;
+use crate::bindings::WalProposer;
+use crate::bindings::WL_SOCKET_READABLE;
+use crate::bindings::WL_SOCKET_WRITEABLE;
+use crate::walproposer::{ApiImpl, WaitResult};
+
+/// Reserved for the internal latch wakeup; real sockets are registered starting from `Token(0)`.
+const LATCH_TOKEN: Token = Token(usize::MAX);
+
+struct Registration {
+    fd: RawFd,
+    sk: *mut Safekeeper,
+}
+
+/// Extracts the fd `EventSet` should poll for a given safekeeper. Supplied by the embedder at
+/// construction time rather than read off `Safekeeper` directly: `EventSet` otherwise never
+/// dereferences its `*mut Safekeeper` pointers (see the `Send` impl below), and the layout of
+/// the generated `Safekeeper` binding is the embedder's to know, not this module's.
+pub type SocketFdAccessor = fn(&Safekeeper) -> RawFd;
+
+/// Multiplexes readiness of every registered safekeeper socket plus an internal latch wakeup
+/// through a single `mio::Poll`, so `wait_event_set` can block on all of them at once.
+pub struct EventSet {
+    poll: Poll,
+    waker: Arc<Waker>,
+    registrations: HashMap<Token, Registration>,
+    /// Reverse of `registrations`, so the `update_event_set`/`add_safekeeper_event_set` callbacks
+    /// (which the C side invokes with only a `*mut Safekeeper`, no `Token`) can find the
+    /// registration to touch.
+    sk_tokens: HashMap<*mut Safekeeper, Token>,
+    next_token: usize,
+    socket_fd: SocketFdAccessor,
+}
+
+impl EventSet {
+    pub fn new(socket_fd: SocketFdAccessor) -> std::io::Result<Self> {
+        let poll = Poll::new()?;
+        let waker = Arc::new(Waker::new(poll.registry(), LATCH_TOKEN)?);
+        Ok(Self {
+            poll,
+            waker,
+            registrations: HashMap::new(),
+            sk_tokens: HashMap::new(),
+            next_token: 0,
+            socket_fd,
+        })
+    }
+
+    /// A cloneable, thread-safe handle that signals the latch, waking a blocked `wait()` with
+    /// `WaitResult::Latch` regardless of what socket activity is pending.
+    pub fn waker(&self) -> Arc<Waker> {
+        self.waker.clone()
+    }
+
+    fn interest_from_events(events: uint32) -> Interest {
+        let mut interest = None;
+        if events & WL_SOCKET_READABLE != 0 {
+            interest = Some(Interest::READABLE);
+        }
+        if events & WL_SOCKET_WRITEABLE != 0 {
+            interest = Some(match interest {
+                Some(existing) => existing.add(Interest::WRITABLE),
+                None => Interest::WRITABLE,
+            });
+        }
+        interest.unwrap_or(Interest::READABLE)
+    }
+
+    /// Registers a newly-connected safekeeper's socket, returning the `Token` the caller should
+    /// stash on the `Safekeeper` so later `update()` calls know which registration to touch.
+    pub fn add_safekeeper(
+        &mut self,
+        sk: *mut Safekeeper,
+        fd: RawFd,
+        events: uint32,
+    ) -> std::io::Result<Token> {
+        let token = Token(self.next_token);
+        self.next_token += 1;
+
+        self.poll
+            .registry()
+            .register(&mut SourceFd(&fd), token, Self::interest_from_events(events))?;
+        self.registrations.insert(token, Registration { fd, sk });
+        self.sk_tokens.insert(sk, token);
+
+        Ok(token)
+    }
+
+    /// Updates the interest mask for an already-registered safekeeper socket.
+    pub fn update(&mut self, token: Token, events: uint32) -> std::io::Result<()> {
+        let Some(registration) = self.registrations.get(&token) else {
+            return Ok(());
+        };
+        self.poll.registry().reregister(
+            &mut SourceFd(&registration.fd),
+            token,
+            Self::interest_from_events(events),
+        )
+    }
+
+    /// Deregisters everything, mirroring `free_event_set`'s "tear down the whole poller" contract.
+    pub fn clear(&mut self) {
+        for registration in self.registrations.values() {
+            let _ = self
+                .poll
+                .registry()
+                .deregister(&mut SourceFd(&registration.fd));
+        }
+        self.registrations.clear();
+        self.sk_tokens.clear();
+    }
+
+    /// Blocks for up to `timeout_millis` (no limit if negative) and returns the first ready
+    /// safekeeper, `WaitResult::Latch` if the waker fired, or `WaitResult::Timeout` otherwise.
+    pub fn wait(&mut self, timeout_millis: i64) -> std::io::Result<WaitResult> {
+        let timeout = if timeout_millis < 0 {
+            None
+        } else {
+            Some(Duration::from_millis(timeout_millis as u64))
+        };
+
+        let mut events = Events::with_capacity(16);
+        self.poll.poll(&mut events, timeout)?;
+
+        for event in events.iter() {
+            if event.token() == LATCH_TOKEN {
+                return Ok(WaitResult::Latch);
+            }
+
+            if let Some(registration) = self.registrations.get(&event.token()) {
+                let mut event_mask = 0;
+                if event.is_readable() {
+                    event_mask |= WL_SOCKET_READABLE;
+                }
+                if event.is_writable() {
+                    event_mask |= WL_SOCKET_WRITEABLE;
+                }
+                return Ok(WaitResult::Network(registration.sk, event_mask));
+            }
+        }
+
+        Ok(WaitResult::Timeout)
+    }
+}
+
+// Safety: `EventSet` is only ever driven from the single thread that runs the walproposer loop;
+// the raw `*mut Safekeeper` pointers it stores are never dereferenced here, only handed back to
+// the caller, which already assumes non-Send/Sync semantics for these pointers elsewhere.
+unsafe impl Send for EventSet {}
+
+impl ApiImpl for EventSet {
+    fn init_event_set(&mut self, _wp: &mut WalProposer) {
+        // The C side calls this once, before any safekeeper has been registered, so there are no
+        // existing registrations to preserve: rebuild the `mio::Poll`/`Waker` pair from scratch
+        // rather than trying to carry the old ones forward.
+        *self = EventSet::new(self.socket_fd).expect("failed to initialize mio event set");
+    }
+
+    fn update_event_set(&mut self, sk: &mut Safekeeper, events: uint32) {
+        let Some(&token) = self.sk_tokens.get(&(sk as *mut Safekeeper)) else {
+            return;
+        };
+        let _ = self.update(token, events);
+    }
+
+    fn add_safekeeper_event_set(&mut self, sk: &mut Safekeeper, events: uint32) {
+        let fd = (self.socket_fd)(sk);
+        let _ = self.add_safekeeper(sk as *mut Safekeeper, fd, events);
+    }
+
+    fn wait_event_set(&mut self, _wp: &mut WalProposer, timeout: i64) -> WaitResult {
+        self.wait(timeout).expect("mio poll failed")
+    }
+
+    fn free_event_set(&mut self, _wp: &mut WalProposer) {
+        self.clear();
+    }
+}
@@ -1,4 +1,4 @@
-use anyhow::{anyhow, Context};
+use anyhow::anyhow;
 use async_trait::async_trait;
 use dashmap::DashMap;
 use futures::future::poll_fn;
@@ -9,7 +9,11 @@ use pbkdf2::{
 };
 use pq_proto::StartupMessageParams;
 use smol_str::SmolStr;
-use std::{collections::HashMap, net::IpAddr, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Weak},
+};
 use std::{
     fmt,
     task::{ready, Poll},
@@ -18,13 +22,15 @@ use std::{
     ops::Deref,
     sync::atomic::{self, AtomicUsize},
 };
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio::time;
 use tokio_postgres::{AsyncMessage, ReadyForQueryStatus};
 
 use crate::{
     auth::{self, backend::ComputeUserInfo, check_peer_addr_is_in_list},
     console,
-    metrics::{LatencyTimer, NUM_DB_CONNECTIONS_GAUGE},
+    metrics::{LatencyTimer, NUM_CONNECTION_RETRIES_COUNTER, NUM_DB_CONNECTIONS_GAUGE},
     proxy::{connect_compute::ConnectMechanism, neon_options},
     usage_metrics::{Ids, MetricCounter, USAGE_METRICS},
 };
@@ -36,6 +42,24 @@ use tracing::{info, info_span, Instrument};
 pub const APP_NAME: &str = "/sql_over_http";
 const MAX_CONNS_PER_ENDPOINT: usize = 20;
 
+/// How long a pooled connection may sit idle (since `_last_access`) before the background
+/// reaper closes it.
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// How long a pooled connection may live in total (since `ClientInner::creation`) before the
+/// reaper closes it, regardless of how recently it was used.
+const DEFAULT_MAX_LIFETIME: Duration = Duration::from_secs(60 * 60);
+/// How often the background reaper walks the pool looking for idle/over-age connections.
+const GC_INTERVAL: Duration = Duration::from_secs(30);
+/// Default time `GlobalConnPool::get` will wait to acquire a per-endpoint semaphore permit
+/// before giving up with `PoolError`.
+const DEFAULT_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of permits released back into an endpoint's semaphore on `shutdown`, so that every
+/// blocked `acquire_owned` waiter wakes up (and then observes `closed` and fails fast) instead of
+/// waiting out its full `acquire_timeout`. Mirrors cdbc's `WAKE_ALL_PERMITS` shutdown trick.
+const WAKE_ALL_PERMITS: usize = 10_000;
+/// Default timeout for the test-on-checkout ping issued when `test_before_acquire` is enabled.
+const DEFAULT_PING_TIMEOUT: Duration = Duration::from_millis(300);
+
 #[derive(Debug, Clone)]
 pub struct ConnInfo {
     pub username: SmolStr,
@@ -59,9 +83,25 @@ impl fmt::Display for ConnInfo {
     }
 }
 
+/// Returned by `GlobalConnPool::get` when a per-endpoint semaphore permit could not be acquired
+/// within `acquire_timeout`, e.g. because the endpoint is already at `max_conns_per_endpoint` and
+/// no connection was returned in time, or because the pool has been shut down.
+#[derive(Debug)]
+pub struct PoolError {
+    endpoint: SmolStr,
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "connection pool for '{}' is exhausted", self.endpoint)
+    }
+}
+
+impl std::error::Error for PoolError {}
+
 struct ConnPoolEntry {
     conn: ClientInner,
-    _last_access: std::time::Instant,
+    _last_access: Instant,
 }
 
 // Per-endpoint connection pool, (dbname, username) -> DbUserConnPool
@@ -69,6 +109,30 @@ struct ConnPoolEntry {
 pub struct EndpointConnPool {
     pools: HashMap<(SmolStr, SmolStr), DbUserConnPool>,
     total_conns: usize,
+    /// Bounds the number of concurrently-live connections (pooled + checked out) for this
+    /// endpoint to `max_conns_per_endpoint`, following the semaphore-based admission control
+    /// used by actix's `ConnectionPool`, cdbc's `SharedPool`, and sqlx's `acquire_timeout`.
+    semaphore: Arc<Semaphore>,
+    /// Number of `acquire_permit` calls currently waiting on `semaphore`. A permit being awaited
+    /// doesn't show up in `semaphore.available_permits()` until it's actually granted, so without
+    /// this, `gc_expired_connections` could decide this pool has no outstanding permits and
+    /// remove it in the same instant a waiter's `acquire_owned` resolves -- orphaning that permit
+    /// on a semaphore no longer reachable through `global_pool`, while the endpoint's next lookup
+    /// spins up a brand-new `EndpointConnPool` (and semaphore) that knows nothing about it.
+    in_flight_permit_acquires: usize,
+}
+
+/// RAII guard keeping `EndpointConnPool::in_flight_permit_acquires` accurate even if the
+/// `acquire_permit` future is dropped mid-wait (e.g. the caller is cancelled), mirroring the
+/// `conn_gauge` guard pattern used for `NUM_DB_CONNECTIONS_GAUGE`.
+struct InFlightPermitGuard {
+    pool: Arc<RwLock<EndpointConnPool>>,
+}
+
+impl Drop for InFlightPermitGuard {
+    fn drop(&mut self) {
+        self.pool.write().in_flight_permit_acquires -= 1;
+    }
 }
 
 /// 4096 is the number of rounds that SCRAM-SHA-256 recommends.
@@ -85,6 +149,11 @@ const PARAMS: Params = Params {
 pub struct DbUserConnPool {
     conns: Vec<ConnPoolEntry>,
     password_hash: Option<PasswordHashString>,
+    /// The `ConnInfo`/peer address of the most recent successful connection for this
+    /// (db, user), retained only so the background pre-warm task (see
+    /// `GlobalConnPool::maybe_spawn_refill`) can re-establish connections up to
+    /// `min_conns_per_db_user` without a client having to supply its password again.
+    last_conn: Option<(ConnInfo, IpAddr)>,
 }
 
 pub struct GlobalConnPool {
@@ -110,17 +179,130 @@ pub struct GlobalConnPool {
     // Using a lock to remove any race conditions.
     // Eg cleaning up connections while a new connection is returned
     closed: RwLock<bool>,
+
+    /// How long a pooled connection may sit idle before the reaper closes it.
+    idle_timeout: Duration,
+    /// How long a pooled connection may live in total before the reaper closes it.
+    max_lifetime: Duration,
+    /// How long `get` will wait to acquire a per-endpoint semaphore permit before giving up.
+    acquire_timeout: Duration,
+    /// Opt-in test-on-checkout: when true, a reused cached connection is pinged before being
+    /// handed back out, and discarded (opening a new one instead) if the ping fails.
+    test_before_acquire: bool,
+    /// Timeout applied to the test-on-checkout ping.
+    ping_timeout: Duration,
+    /// Minimum number of idle connections to keep warm per (db, user), refilled in the
+    /// background after a connection is checked out or reaped. `0` disables pre-warming.
+    min_conns_per_db_user: usize,
+
+    /// Connections checked out in `PoolingMode::Session`, keyed by the caller's session key.
+    /// Excluded from `global_pool` for as long as the session is open; see `get_session`.
+    session_pool: DashMap<SmolStr, ClientInner>,
 }
 
 impl GlobalConnPool {
     pub fn new(config: &'static crate::config::ProxyConfig) -> Arc<Self> {
-        Arc::new(Self {
+        let pool = Arc::new(Self {
             global_pool: DashMap::new(),
             global_pool_size: AtomicUsize::new(0),
             max_conns_per_endpoint: MAX_CONNS_PER_ENDPOINT,
             proxy_config: config,
             closed: RwLock::new(false),
-        })
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            max_lifetime: DEFAULT_MAX_LIFETIME,
+            acquire_timeout: DEFAULT_ACQUIRE_TIMEOUT,
+            test_before_acquire: false,
+            ping_timeout: DEFAULT_PING_TIMEOUT,
+            min_conns_per_db_user: 0,
+            session_pool: DashMap::new(),
+        });
+
+        pool.clone().spawn_reaper();
+
+        pool
+    }
+
+    /// Spawns the background task that periodically reaps idle and over-age connections, modeled
+    /// on hyper's interval-driven pool cleanup. Holds only a `Weak` reference so the task exits
+    /// on its own once the pool is dropped, instead of keeping it alive forever.
+    fn spawn_reaper(self: Arc<Self>) {
+        let pool: Weak<Self> = Arc::downgrade(&self);
+        drop(self);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(GC_INTERVAL);
+            loop {
+                interval.tick().await;
+                let Some(pool) = pool.upgrade() else {
+                    return;
+                };
+                pool.gc_expired_connections();
+            }
+        });
+    }
+
+    /// Walks the pool one endpoint at a time, dropping any `ConnPoolEntry` whose `_last_access`
+    /// is older than `idle_timeout` or whose connection has lived longer than `max_lifetime`.
+    /// An `EndpointConnPool` is only removed from `global_pool` once it holds zero idle
+    /// connections *and* zero outstanding semaphore permits — `total_conns` alone only counts
+    /// idle pooled connections, so a checked-out connection with nothing left in `pools` would
+    /// otherwise get its `EndpointConnPool` (and the semaphore its permit belongs to) reaped out
+    /// from under it, orphaning that permit and desyncing the per-endpoint bound.
+    fn gc_expired_connections(self: &Arc<Self>) {
+        let now = Instant::now();
+
+        self.global_pool.retain(|endpoint, endpoint_pool| {
+            let (keep, refill_targets) = {
+                let mut pool = endpoint_pool.write();
+                let has_outstanding_permits = pool.semaphore.available_permits()
+                    < self.max_conns_per_endpoint
+                    || pool.in_flight_permit_acquires > 0;
+                if pool.total_conns == 0 {
+                    (has_outstanding_permits, Vec::new())
+                } else {
+                    let mut reaped = 0;
+                    let mut touched = Vec::new();
+                    for (db_user, db_user_pool) in pool.pools.iter_mut() {
+                        let before = db_user_pool.conns.len();
+                        db_user_pool.conns.retain(|entry| {
+                            let idle_expired =
+                                now.duration_since(entry._last_access) > self.idle_timeout;
+                            let lifetime_expired =
+                                now.duration_since(entry.conn.creation) > self.max_lifetime;
+                            !(idle_expired || lifetime_expired)
+                        });
+                        let this_reaped = before - db_user_pool.conns.len();
+                        if this_reaped > 0 {
+                            reaped += this_reaped;
+                            touched.push(db_user.clone());
+                        }
+                    }
+
+                    if reaped > 0 {
+                        pool.total_conns -= reaped;
+                        info!(
+                            "pool: reaped {reaped} idle/over-age connection(s) for '{endpoint}', total_conns={}",
+                            pool.total_conns
+                        );
+                    }
+
+                    (pool.total_conns != 0 || has_outstanding_permits, touched)
+                }
+            };
+
+            if !keep {
+                // nothing left to reap here; also drop the now-useless map entry
+                self.global_pool_size
+                    .fetch_sub(1, atomic::Ordering::Relaxed);
+            } else {
+                // top pools that just lost connections back up to min_conns_per_db_user
+                for db_user in refill_targets {
+                    self.maybe_spawn_refill(endpoint_pool.clone(), db_user);
+                }
+            }
+
+            keep
+        });
     }
 
     pub fn shutdown(&self) {
@@ -133,8 +315,50 @@ impl GlobalConnPool {
             pool.pools.clear();
             pool.total_conns = 0;
 
+            // wake every acquire_permit waiter so it observes `closed` and fails fast instead of
+            // blocking until its acquire_timeout expires.
+            pool.semaphore.add_permits(WAKE_ALL_PERMITS);
+
             false
         });
+
+        self.session_pool.clear();
+    }
+
+    /// Acquires a permit from the given endpoint's semaphore, bounding the number of
+    /// concurrently-live connections to `max_conns_per_endpoint`. Returns `PoolError` if no
+    /// permit becomes available within `acquire_timeout`, or if the pool is shut down while
+    /// waiting.
+    async fn acquire_permit(&self, conn_info: &ConnInfo) -> anyhow::Result<OwnedSemaphorePermit> {
+        let pool = self.get_or_create_endpoint_pool(&conn_info.hostname);
+        let semaphore = {
+            let mut guard = pool.write();
+            guard.in_flight_permit_acquires += 1;
+            guard.semaphore.clone()
+        };
+        // Registers intent to acquire before awaiting, and keeps `in_flight_permit_acquires`
+        // accurate for as long as this call (or its cancellation) takes: see the comment on
+        // `EndpointConnPool::in_flight_permit_acquires`.
+        let _in_flight_guard = InFlightPermitGuard { pool: pool.clone() };
+
+        let permit = match time::timeout(self.acquire_timeout, semaphore.acquire_owned()).await {
+            Ok(Ok(permit)) => permit,
+            Ok(Err(_closed)) | Err(_elapsed) => {
+                return Err(PoolError {
+                    endpoint: conn_info.hostname.clone(),
+                }
+                .into())
+            }
+        };
+
+        if *self.closed.read() {
+            return Err(PoolError {
+                endpoint: conn_info.hostname.clone(),
+            }
+            .into());
+        }
+
+        Ok(permit)
     }
 
     pub async fn get(
@@ -196,6 +420,16 @@ impl GlobalConnPool {
             if client.inner.is_closed() {
                 let conn_id = uuid::Uuid::new_v4();
                 info!(%conn_id, "pool: cached connection '{conn_info}' is closed, opening a new one");
+                // release the stale `client`'s permit before acquiring a fresh one for the
+                // replacement, instead of letting it linger until this scope ends: under a burst
+                // of simultaneous replacements that would transiently double-hold a permit per
+                // connection and could pin every permit in the endpoint's semaphore.
+                client.close_hard();
+                let permit = if force_new {
+                    None
+                } else {
+                    Some(self.acquire_permit(conn_info).await?)
+                };
                 connect_to_compute(
                     self.proxy_config,
                     conn_info,
@@ -205,20 +439,63 @@ impl GlobalConnPool {
                     peer_addr,
                 )
                 .await
+                .map(|inner| inner.with_permit(permit))
             } else {
-                info!("pool: reusing connection '{conn_info}'");
-                client.session.send(session_id)?;
-                tracing::Span::current().record(
-                    "pid",
-                    &tracing::field::display(client.inner.get_process_id()),
-                );
-                latency_timer.pool_hit();
-                latency_timer.success();
-                return Ok(Client::new(client, pool).await);
+                let validated = if self.test_before_acquire {
+                    client.ping(self.ping_timeout).await
+                } else {
+                    true
+                };
+
+                if validated {
+                    info!("pool: reusing connection '{conn_info}'");
+                    client.session.send(session_id)?;
+                    tracing::Span::current().record(
+                        "pid",
+                        &tracing::field::display(client.inner.get_process_id()),
+                    );
+                    latency_timer.pool_hit();
+                    latency_timer.success();
+                    // we just popped an entry out of the pool; top it back up to
+                    // min_conns_per_db_user in the background if needed.
+                    self.maybe_spawn_refill(
+                        self.get_or_create_endpoint_pool(&conn_info.hostname),
+                        conn_info.db_and_user(),
+                    );
+                    // `client` already carries the permit it was issued when it was first
+                    // established; reusing it here keeps it checked out of the semaphore.
+                    return Ok(Client::new(client, pool).await);
+                }
+
+                let conn_id = uuid::Uuid::new_v4();
+                info!(%conn_id, "pool: cached connection '{conn_info}' failed validation, opening a new one");
+                // the failed `client` is abandoned (not gracefully closed) and its permit
+                // released with it; acquire a fresh one for the replacement.
+                client.close_hard();
+                let permit = if force_new {
+                    None
+                } else {
+                    Some(self.acquire_permit(conn_info).await?)
+                };
+                connect_to_compute(
+                    self.proxy_config,
+                    conn_info,
+                    conn_id,
+                    session_id,
+                    latency_timer,
+                    peer_addr,
+                )
+                .await
+                .map(|inner| inner.with_permit(permit))
             }
         } else {
             let conn_id = uuid::Uuid::new_v4();
             info!(%conn_id, "pool: opening a new connection '{conn_info}'");
+            let permit = if force_new {
+                None
+            } else {
+                Some(self.acquire_permit(conn_info).await?)
+            };
             connect_to_compute(
                 self.proxy_config,
                 conn_info,
@@ -228,6 +505,7 @@ impl GlobalConnPool {
                 peer_addr,
             )
             .await
+            .map(|inner| inner.with_permit(permit))
         };
         if let Ok(client) = &new_client {
             tracing::Span::current().record(
@@ -268,10 +546,116 @@ impl GlobalConnPool {
             }
             _ => {}
         }
+
+        if new_client.is_ok() && !force_new {
+            let endpoint_pool = self.get_or_create_endpoint_pool(&conn_info.hostname);
+            {
+                let mut endpoint_pool = endpoint_pool.write();
+                endpoint_pool
+                    .pools
+                    .entry(conn_info.db_and_user())
+                    .or_default()
+                    .last_conn = Some((conn_info.clone(), peer_addr));
+            }
+            self.maybe_spawn_refill(endpoint_pool, conn_info.db_and_user());
+        }
+
         let new_client = new_client?;
         Ok(Client::new(new_client, pool).await)
     }
 
+    /// Checks out a connection pinned to `session_key` for as long as the caller holds onto it,
+    /// following pgcat's `pool_mode = "session"`. If a connection is already parked under this
+    /// key in `session_pool` (left there by a previous request in the same session), it's reused
+    /// as-is; otherwise a connection is checked out through the regular `get` path (so it still
+    /// goes through the semaphore, validation, etc.) and switched into session mode. Call
+    /// `Client::end_session` once the caller's session ends to `DISCARD ALL` and return the
+    /// connection to the shared pool.
+    pub async fn get_session(
+        self: &Arc<Self>,
+        conn_info: &ConnInfo,
+        session_key: SmolStr,
+        session_id: uuid::Uuid,
+        peer_addr: IpAddr,
+    ) -> anyhow::Result<Client> {
+        if let Some((_, inner)) = self.session_pool.remove(&session_key) {
+            inner.session.send(session_id)?;
+            return Ok(Client::new(inner, Some((conn_info.clone(), self.clone())))
+                .await
+                .into_session(session_key));
+        }
+
+        let client = self.get(conn_info, false, session_id, peer_addr).await?;
+        Ok(client.into_session(session_key))
+    }
+
+    /// If the (db, user) pool named by `db_user` is below `min_conns_per_db_user`, spawns
+    /// background tasks to connect up to the minimum, reusing the most recent successful
+    /// `ConnInfo` for that pool so callers don't pay full `wake_compute` + TLS + auth latency on
+    /// their next request. Guarded by `closed` and by `max_conns_per_endpoint` so a refill can
+    /// never push the endpoint over its cap.
+    fn maybe_spawn_refill(
+        self: &Arc<Self>,
+        endpoint_pool: Arc<RwLock<EndpointConnPool>>,
+        db_user: (SmolStr, SmolStr),
+    ) {
+        if self.min_conns_per_db_user == 0 || *self.closed.read() {
+            return;
+        }
+
+        let (conn_info, peer_addr, to_spawn) = {
+            let pool = endpoint_pool.read();
+            let Some(entries) = pool.pools.get(&db_user) else {
+                return;
+            };
+            let Some((conn_info, peer_addr)) = entries.last_conn.clone() else {
+                return;
+            };
+            if entries.conns.len() >= self.min_conns_per_db_user {
+                return;
+            }
+
+            let room = self.max_conns_per_endpoint.saturating_sub(pool.total_conns);
+            let to_spawn = (self.min_conns_per_db_user - entries.conns.len()).min(room);
+            (conn_info, peer_addr, to_spawn)
+        };
+
+        for _ in 0..to_spawn {
+            let this = self.clone();
+            let conn_info = conn_info.clone();
+            tokio::spawn(async move {
+                if *this.closed.read() {
+                    return;
+                }
+
+                let conn_id = uuid::Uuid::new_v4();
+                let permit = match this.acquire_permit(&conn_info).await {
+                    Ok(permit) => permit,
+                    Err(_) => return,
+                };
+
+                match connect_to_compute(
+                    this.proxy_config,
+                    &conn_info,
+                    conn_id,
+                    uuid::Uuid::new_v4(),
+                    LatencyTimer::new("http"),
+                    peer_addr,
+                )
+                .await
+                {
+                    Ok(inner) => {
+                        info!(%conn_id, "pool: pre-warmed connection '{conn_info}'");
+                        let _ = this.put(&conn_info, inner.with_permit(Some(permit)));
+                    }
+                    Err(e) => {
+                        warn!(%conn_id, "pool: failed to pre-warm connection for '{conn_info}': {e}");
+                    }
+                }
+            });
+        }
+    }
+
     fn put(&self, conn_info: &ConnInfo, client: ClientInner) -> anyhow::Result<()> {
         let conn_id = client.conn_id;
 
@@ -301,7 +685,7 @@ impl GlobalConnPool {
                 if let Some(pool_entries) = pool.pools.get_mut(&conn_info.db_and_user()) {
                     pool_entries.conns.push(ConnPoolEntry {
                         conn: client,
-                        _last_access: std::time::Instant::now(),
+                        _last_access: Instant::now(),
                     });
 
                     returned = true;
@@ -334,6 +718,8 @@ impl GlobalConnPool {
         let new_pool = Arc::new(RwLock::new(EndpointConnPool {
             pools: HashMap::new(),
             total_conns: 0,
+            semaphore: Arc::new(Semaphore::new(self.max_conns_per_endpoint)),
+            in_flight_permit_acquires: 0,
         }));
 
         // find or create a pool for this endpoint
@@ -392,6 +778,25 @@ impl ConnectMechanism for TokioMechanism<'_> {
     fn update_connect_config(&self, _config: &mut compute::ConnCfg) {}
 }
 
+/// Exponential backoff for `connect_to_compute`'s retry loop: `base * 2^(attempt - 1)`.
+fn retry_backoff(config: &config::ProxyConfig, attempt: u32) -> Duration {
+    config.connect_to_compute_base_backoff * 2u32.saturating_pow(attempt - 1)
+}
+
+/// Whether a failed compute connection attempt is worth retrying. A `tokio_postgres::Error`
+/// only carries an `SqlState` once the server has actually answered and rejected us (wrong
+/// password, unknown database, ...) -- that's deterministic and retrying would just waste an
+/// attempt, so it must still reach `get`'s `password_hash` invalidation on the first try.
+/// Anything else -- connection refused/reset, a connect timeout, a DNS failure -- never got that
+/// far, so the compute node is plausibly just still starting up (following dozer's
+/// retry-on-network-errors approach for the Postgres connector).
+fn is_retryable_connect_error(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<tokio_postgres::Error>() {
+        Some(pg_err) => pg_err.code().is_none(),
+        None => false,
+    }
+}
+
 // Wake up the destination if needed. Code here is a bit involved because
 // we reuse the code from the usual proxy and we need to prepare few structures
 // that this code expects.
@@ -437,23 +842,67 @@ async fn connect_to_compute(
             return Err(auth::AuthError::ip_address_not_allowed().into());
         }
     }
-    let node_info = backend
-        .wake_compute(&extra)
-        .await?
-        .context("missing cache entry from wake_compute")?;
 
-    crate::proxy::connect_compute::connect_to_compute(
-        &TokioMechanism {
-            conn_id,
-            conn_info,
-            session_id,
-        },
-        node_info,
-        &extra,
-        &backend,
-        latency_timer,
-    )
-    .await
+    let mechanism = TokioMechanism {
+        conn_id,
+        conn_info,
+        session_id,
+    };
+
+    // The first attempt reuses the timer the caller handed us; retries (the compute node is
+    // plausibly still starting up) each get their own fresh timer, same as `LatencyTimer::new`
+    // is already used for other one-off connect attempts in this module.
+    let mut latency_timer = Some(latency_timer);
+    let mut attempt: u32 = 0;
+    loop {
+        let node_info = match backend.wake_compute(&extra).await? {
+            Some(node_info) => node_info,
+            None if attempt < config.connect_to_compute_retries => {
+                attempt += 1;
+                NUM_CONNECTION_RETRIES_COUNTER
+                    .with_label_values(&["http"])
+                    .inc();
+                let backoff = retry_backoff(config, attempt);
+                warn!(attempt, ?backoff, "pool: wake_compute returned no cache entry, retrying");
+                time::sleep(backoff).await;
+                continue;
+            }
+            None => return Err(anyhow!("missing cache entry from wake_compute")),
+        };
+
+        let attempt_timer = latency_timer
+            .take()
+            .unwrap_or_else(|| LatencyTimer::new("http"));
+        match crate::proxy::connect_compute::connect_to_compute(
+            &mechanism,
+            node_info,
+            &extra,
+            &backend,
+            attempt_timer,
+        )
+        .await
+        {
+            Ok(client) => return Ok(client),
+            Err(e)
+                if attempt < config.connect_to_compute_retries
+                    && is_retryable_connect_error(&e) =>
+            {
+                attempt += 1;
+                NUM_CONNECTION_RETRIES_COUNTER
+                    .with_label_values(&["http"])
+                    .inc();
+                let backoff = retry_backoff(config, attempt);
+                warn!(
+                    attempt,
+                    ?backoff,
+                    error = %e,
+                    "pool: transient error connecting to compute, retrying"
+                );
+                time::sleep(backoff).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
 }
 
 async fn connect_to_compute_once(
@@ -490,7 +939,7 @@ async fn connect_to_compute_once(
         branch_id: node_info.aux.branch_id.clone(),
     };
 
-    tokio::spawn(
+    let conn_task = tokio::spawn(
         async move {
             let _conn_gauge = conn_gauge;
             poll_fn(move |cx| {
@@ -532,6 +981,9 @@ async fn connect_to_compute_once(
         session: tx,
         ids,
         conn_id,
+        creation: Instant::now(),
+        permit: None,
+        conn_task,
     })
 }
 
@@ -540,6 +992,60 @@ struct ClientInner {
     session: tokio::sync::watch::Sender<uuid::Uuid>,
     ids: Ids,
     conn_id: uuid::Uuid,
+    /// When this connection was established, used to enforce `GlobalConnPool::max_lifetime`
+    /// independently of how recently it was last used.
+    creation: Instant,
+    /// The per-endpoint semaphore permit this connection checked out, if it was established
+    /// through the pool. Held for the lifetime of the connection and released automatically
+    /// (returning the slot to the semaphore) whenever this `ClientInner` is dropped, whether
+    /// that's because it was thrown away or because the pool itself is being torn down.
+    permit: Option<OwnedSemaphorePermit>,
+    /// The task driving `Connection::poll_message` for this backend socket. `close_hard` aborts
+    /// it directly instead of relying on `inner`'s `Drop` impl, which only stops feeding the task
+    /// new work -- the task itself (and the socket it owns) would otherwise keep running until it
+    /// next polls and notices the client side is gone.
+    conn_task: tokio::task::JoinHandle<()>,
+}
+
+impl ClientInner {
+    fn with_permit(mut self, permit: Option<OwnedSemaphorePermit>) -> Self {
+        self.permit = permit;
+        self
+    }
+
+    /// A cheap round-trip used as a test-on-checkout validation, catching pooled connections that
+    /// are half-open, stuck mid-protocol, or were killed server-side without a TCP reset -- none
+    /// of which `tokio_postgres::Client::is_closed` detects on its own. Requiring the backend to
+    /// report `ReadyForQueryStatus::Idle` (rather than just that the round-trip completed at all)
+    /// also catches a connection left mid-transaction or mid-copy by a previous borrower.
+    async fn ping(&self, timeout: Duration) -> bool {
+        let Ok(result) = time::timeout(timeout, self.inner.simple_query_raw("")).await else {
+            return false;
+        };
+        let Ok(stream) = result else {
+            return false;
+        };
+        use futures::stream::TryStreamExt;
+        let mut stream = std::pin::pin!(stream);
+        loop {
+            match stream.as_mut().try_next().await {
+                Ok(Some(_)) => continue,
+                Ok(None) => return stream.ready_status() == ReadyForQueryStatus::Idle,
+                Err(_) => return false,
+            }
+        }
+    }
+
+    /// Abandons the backend connection without the graceful `Terminate` handshake (sqlx's
+    /// `close_hard`, PR #1901). Used in `GlobalConnPool::get` when a cached connection turns out
+    /// to be closed or fails test-on-checkout validation, where the connection is already known
+    /// or suspected to be broken and waiting on a clean close would just be time spent talking to
+    /// a peer we no longer trust. `Discard::discard` does not call it.
+    /// Aborts `conn_task` directly rather than just dropping `self`: dropping only stops feeding
+    /// the connection-driving task new work, it doesn't make the task (or its socket) go away.
+    fn close_hard(self) {
+        self.conn_task.abort();
+    }
 }
 
 impl Client {
@@ -548,15 +1054,33 @@ impl Client {
     }
 }
 
+/// Pooling behavior for a checked-out `Client`, modeled on pgcat's `pool_mode`.
+#[derive(Debug, Clone)]
+pub enum PoolingMode {
+    /// The default: returned to the shared `EndpointConnPool` as soon as the `Client` is
+    /// dropped, same as today.
+    Transaction,
+    /// Pinned to `SmolStr` (a caller-provided session key) for the lifetime of the session:
+    /// dropping a session-mode `Client` stashes the connection in
+    /// `GlobalConnPool::session_pool` under that key instead of the shared pool, so the next
+    /// `GlobalConnPool::get_session` call with the same key resumes the same backend session
+    /// (stable `SET`s, temp tables, `LISTEN`, advisory locks, server-side prepares). Call
+    /// `Client::end_session` once the caller's session is actually over to `DISCARD ALL` and
+    /// return the connection to the shared pool.
+    Session(SmolStr),
+}
+
 pub struct Client {
     conn_id: uuid::Uuid,
     span: Span,
     inner: Option<ClientInner>,
     pool: Option<(ConnInfo, Arc<GlobalConnPool>)>,
+    mode: PoolingMode,
 }
 
 pub struct Discard<'a> {
     conn_id: uuid::Uuid,
+    mode: &'a PoolingMode,
     pool: &'a mut Option<(ConnInfo, Arc<GlobalConnPool>)>,
 }
 
@@ -570,13 +1094,24 @@ impl Client {
             inner: Some(inner),
             span: Span::current(),
             pool,
+            mode: PoolingMode::Transaction,
         }
     }
+
+    /// Re-pins an already checked-out `Client` to `session_key`, switching it into
+    /// `PoolingMode::Session` so it's returned to `GlobalConnPool::session_pool` instead of the
+    /// shared pool on drop. See `GlobalConnPool::get_session`.
+    pub(self) fn into_session(mut self, session_key: SmolStr) -> Self {
+        self.mode = PoolingMode::Session(session_key);
+        self
+    }
+
     pub fn inner(&mut self) -> (&mut tokio_postgres::Client, Discard<'_>) {
         let Self {
             inner,
             pool,
             conn_id,
+            mode,
             span: _,
         } = self;
         (
@@ -587,6 +1122,7 @@ impl Client {
             Discard {
                 pool,
                 conn_id: *conn_id,
+                mode,
             },
         )
     }
@@ -597,10 +1133,40 @@ impl Client {
     pub fn discard(&mut self) {
         self.inner().1.discard()
     }
+
+    /// Ends a session-pinned session: resets session-local state with `DISCARD ALL` and returns
+    /// the connection to the shared pool, rather than stashing it in `session_pool` for the next
+    /// call with the same session key. A no-op (same as just dropping) in `PoolingMode::Transaction`.
+    pub async fn end_session(mut self) -> anyhow::Result<()> {
+        if matches!(self.mode, PoolingMode::Transaction) || self.pool.is_none() {
+            return Ok(());
+        }
+        // `inner` stays in place so `Client::drop` (which expects it) does the actual hand-off.
+        let client = self
+            .inner
+            .as_ref()
+            .expect("client inner should not be removed");
+        if client.inner.simple_query("DISCARD ALL").await.is_err() {
+            // abandon it the way `ClientInner::close_hard` does; with `pool` taken, `Drop`
+            // won't `put` it back either.
+            client.conn_task.abort();
+            self.pool.take();
+        } else {
+            // back to the shared pool through `Drop`'s regular path instead of `session_pool`
+            self.mode = PoolingMode::Transaction;
+        }
+        Ok(())
+    }
 }
 
 impl Discard<'_> {
     pub fn check_idle(&mut self, status: ReadyForQueryStatus) {
+        // a session-pinned connection is expected to carry state (SET, temp tables, etc.)
+        // across statements within the session, so a non-idle status mid-session is not on its
+        // own a reason to throw the connection away.
+        if matches!(self.mode, PoolingMode::Session(_)) {
+            return;
+        }
         if status != ReadyForQueryStatus::Idle {
             if let Some((conn_info, _)) = self.pool.take() {
                 info!(conn_id = %self.conn_id, "pool: throwing away connection '{conn_info}' because connection is not idle")
@@ -608,6 +1174,10 @@ impl Discard<'_> {
         }
     }
     pub fn discard(&mut self) {
+        // Taking `pool` here means `Client::drop` will simply drop its `ClientInner` instead of
+        // calling `put`, since a connection reaching `discard` is already known or suspected to
+        // be in a broken state. This is a plain drop, not `ClientInner::close_hard`: `conn_task`
+        // is not aborted.
         if let Some((conn_info, _)) = self.pool.take() {
             info!(conn_id = %self.conn_id, "pool: throwing away connection '{conn_info}' because connection is potentially in a broken state")
         }
@@ -633,12 +1203,22 @@ impl Drop for Client {
             .take()
             .expect("client inner should not be removed");
         if let Some((conn_info, conn_pool)) = self.pool.take() {
-            let current_span = self.span.clone();
-            // return connection to the pool
-            tokio::task::spawn_blocking(move || {
-                let _span = current_span.enter();
-                let _ = conn_pool.put(&conn_info, client);
-            });
+            match &self.mode {
+                PoolingMode::Transaction => {
+                    let current_span = self.span.clone();
+                    // return connection to the pool
+                    tokio::task::spawn_blocking(move || {
+                        let _span = current_span.enter();
+                        let _ = conn_pool.put(&conn_info, client);
+                    });
+                }
+                PoolingMode::Session(session_key) => {
+                    // the session isn't over yet (that's `Client::end_session`'s job); stash the
+                    // connection for the next `get_session` call with this same key instead of
+                    // making it visible to the shared pool.
+                    conn_pool.session_pool.insert(session_key.clone(), client);
+                }
+            }
         }
     }
 }